@@ -4,6 +4,7 @@ use anyhow::Error;
 use axum::{Router, extract::State, http::StatusCode, routing::post};
 use base64::{Engine as _, engine::general_purpose::STANDARD};
 use chrono::{Datelike, Duration, Local, NaiveTime, Utc, Weekday};
+use common::metrics::record_external_call;
 use hmac::{Hmac, Mac};
 use rand::{Rng, distr::Alphanumeric};
 use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
@@ -44,17 +45,20 @@ async fn conference_create(state: State<AppState>) -> Result<(), (StatusCode, &'
         .json(&json_str)
         .send()
         .await
+        .inspect_err(|_| record_external_call("huawei_meeting", "create_conference", "error"))
         .unwrap();
 
     let body = res.text().await.unwrap();
     match serde_json::from_str::<Vec<Conferences>>(&body) {
         Ok(conf) => {
+            record_external_call("huawei_meeting", "create_conference", "success");
             tracing::debug!("Create Meeting Return: {}", body);
             let a_model: conference::ActiveModel = conf.first().unwrap().to_owned().into();
             let conf_stg = state.context.conf_stg();
             conf_stg.save_conf(a_model).await.unwrap();
         }
         Err(err) => {
+            record_external_call("huawei_meeting", "create_conference", "invalid_response");
             tracing::error!("parsing err:{}", err);
             tracing::error!("huaweimeeting api return:{}", body);
         }
@@ -106,12 +110,17 @@ async fn app_auth() -> Result<AppAuth, Error> {
         .json(&json_str)
         .send()
         .await
+        .inspect_err(|_| record_external_call("huawei_meeting", "app_auth", "error"))
         .unwrap();
 
     let body = res.text().await.unwrap();
     match serde_json::from_str::<AppAuth>(&body) {
-        Ok(app_auth) => Ok(app_auth),
+        Ok(app_auth) => {
+            record_external_call("huawei_meeting", "app_auth", "success");
+            Ok(app_auth)
+        }
         Err(err) => {
+            record_external_call("huawei_meeting", "app_auth", "invalid_response");
             tracing::error!("parsing err:{}", err);
             tracing::error!("huaweimeetng api return:{}", body);
             Err(err.into())
@@ -143,12 +152,17 @@ async fn account_auth() -> Result<AppAuth, Error> {
         .json(&json_str)
         .send()
         .await
+        .inspect_err(|_| record_external_call("huawei_meeting", "account_auth", "error"))
         .unwrap();
 
     let body = res.text().await.unwrap();
     match serde_json::from_str::<AppAuth>(&body) {
-        Ok(app_auth) => Ok(app_auth),
+        Ok(app_auth) => {
+            record_external_call("huawei_meeting", "account_auth", "success");
+            Ok(app_auth)
+        }
         Err(err) => {
+            record_external_call("huawei_meeting", "account_auth", "invalid_response");
             tracing::error!("parsing err:{}", err);
             tracing::error!("huaweimeetng api return:{}", body);
             Err(err.into())
@@ -5,6 +5,7 @@ use std::{env, fs, vec};
 use anyhow::{Context, Error};
 use axum::extract::State;
 use chrono::{Datelike, NaiveDate};
+use common::metrics::record_email;
 use entity::sea_orm_active_enums::TaskStatus;
 use entity::{student, task};
 use lettre::message::{Attachment, Body, MultiPart, SinglePart, header};
@@ -16,6 +17,11 @@ use service::storage::mentor_stg::{MentorRes, MentorStatus};
 use tera::Tera;
 
 use crate::AppState;
+use crate::report::{
+    HeaderLang, XLSX_CONTENT_TYPE,
+    payout::PayoutReport,
+    statement::{PDF_CONTENT_TYPE, Statement},
+};
 
 enum Lang {
     Zh,
@@ -96,10 +102,18 @@ pub enum EmailContent {
     },
 }
 
+/// 邮件附件，仅本地模板发送时生效
+pub struct EmailAttachment {
+    pub file_name: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
 pub struct EmailSender {
     content: EmailContent,
     receivers: Vec<String>,
     cc_email: Vec<String>,
+    attachments: Vec<EmailAttachment>,
 }
 
 impl EmailSender {
@@ -109,10 +123,27 @@ impl EmailSender {
         context: tera::Context,
         receiver: &str,
         cc_email: Vec<String>,
+    ) -> Self {
+        Self::from_local_template_to_many(
+            template_name,
+            subject,
+            context,
+            vec![receiver.to_string()],
+            cc_email,
+        )
+    }
+
+    pub fn from_local_template_to_many(
+        template_name: &str,
+        subject: &str,
+        context: tera::Context,
+        receivers: Vec<String>,
+        cc_email: Vec<String>,
     ) -> Self {
         Self {
-            receivers: vec![receiver.to_string()],
+            receivers,
             cc_email,
+            attachments: vec![],
             content: EmailContent::LocalTemplate {
                 template_name: template_name.to_string(),
                 subject: subject.to_string(),
@@ -130,6 +161,7 @@ impl EmailSender {
         Self {
             receivers,
             cc_email,
+            attachments: vec![],
             content: EmailContent::ZeptoTemplate {
                 template_id: template_id.to_string(),
                 variables,
@@ -137,13 +169,25 @@ impl EmailSender {
         }
     }
 
+    pub fn with_attachment(mut self, file_name: &str, content_type: &str, data: Vec<u8>) -> Self {
+        self.attachments.push(EmailAttachment {
+            file_name: file_name.to_owned(),
+            content_type: content_type.to_owned(),
+            data,
+        });
+        self
+    }
+
     pub async fn send(&self) -> Result<(), Error> {
         match &self.content {
             EmailContent::LocalTemplate {
                 template_name,
                 subject,
                 context,
-            } => self.send_local(template_name, subject, context).await,
+            } => self
+                .send_local(template_name, subject, context)
+                .await
+                .inspect_err(|_| record_email(template_name, false)),
 
             EmailContent::ZeptoTemplate {
                 template_id,
@@ -175,8 +219,11 @@ impl EmailSender {
 
         let mut email_builder = Message::builder()
             .from("no-reply@r2cn.dev".parse().unwrap())
-            .to(self.receivers[0].parse().unwrap())
             .subject(subject);
+        for receiver in &self.receivers {
+            email_builder = email_builder.to(receiver.parse()?);
+        }
+        let receivers = self.receivers.join(",");
 
         for cc_addr in &self.cc_email {
             match cc_addr.parse() {
@@ -199,9 +246,17 @@ impl EmailSender {
                     Err(err) => tracing::warn!("内嵌图片加载失败 {}: {}", img_path, err),
                 }
             }
-            email_builder.multipart(multipart)
-        } else {
+            if self.attachments.is_empty() {
+                email_builder.multipart(multipart)
+            } else {
+                email_builder
+                    .multipart(self.with_attachments(MultiPart::mixed().multipart(multipart))?)
+            }
+        } else if self.attachments.is_empty() {
             email_builder.singlepart(html_part)
+        } else {
+            email_builder
+                .multipart(self.with_attachments(MultiPart::mixed().singlepart(html_part))?)
         }?;
 
         let creds = Credentials::new(env::var("ZEPTO_AK").unwrap(), env::var("ZEPTO_SK").unwrap());
@@ -212,13 +267,31 @@ impl EmailSender {
             .build();
 
         match mailer.send(&email) {
-            Ok(_) => tracing::info!("邮件发送成功: to {} ", self.receivers[0]),
-            Err(e) => tracing::error!("邮件发送失败: {:?}, to {}", e, self.receivers[0]),
+            Ok(_) => {
+                record_email(template_name, true);
+                tracing::info!("邮件发送成功: to {} ", receivers)
+            }
+            Err(e) => {
+                record_email(template_name, false);
+                tracing::error!("邮件发送失败: {:?}, to {}", e, receivers)
+            }
         }
 
         Ok(())
     }
 
+    /// 在 multipart/mixed 中追加附件
+    fn with_attachments(&self, mut body: MultiPart) -> Result<MultiPart, Error> {
+        for attachment in &self.attachments {
+            let content_type = header::ContentType::parse(&attachment.content_type)?;
+            body = body.singlepart(
+                Attachment::new(attachment.file_name.clone())
+                    .body(attachment.data.clone(), content_type),
+            );
+        }
+        Ok(body)
+    }
+
     async fn send_zeptomail(
         &self,
         template_id: &str,
@@ -250,8 +323,10 @@ impl EmailSender {
             )
             .json(&body)
             .send()
-            .await?;
+            .await
+            .inspect_err(|_| record_email(template_id, false))?;
 
+        record_email(template_id, resp.status().is_success());
         if resp.status().is_success() {
             Ok(())
         } else {
@@ -261,10 +336,15 @@ impl EmailSender {
         }
     }
 
-    pub async fn notice_all_email(state: State<AppState>, template_id: &str) -> Result<(), Error> {
+    /// program_id 不为空时只通知该项目的导师和学生
+    pub async fn notice_all_email(
+        state: State<AppState>,
+        template_id: &str,
+        program_id: Option<i32>,
+    ) -> Result<(), Error> {
         let active_mentor_emails: Vec<String> = state
             .mentor_stg()
-            .get_active_mentors()
+            .get_active_mentors(program_id)
             .await
             .unwrap()
             .iter()
@@ -273,7 +353,7 @@ impl EmailSender {
 
         let active_student_emails: Vec<String> = state
             .student_stg()
-            .get_active_students()
+            .get_active_students(program_id)
             .await
             .unwrap()
             .iter()
@@ -434,6 +514,8 @@ impl EmailSender {
                 .await
                 .unwrap();
 
+            let statement = Statement::new(&student, &last_month, &finished_tasks_last_month);
+
             let mentor_logins = finished_tasks_last_month
                 .iter()
                 .map(|t| t.mentor_github_login.clone())
@@ -469,9 +551,51 @@ impl EmailSender {
                 &student.email,
                 active_mentor_emails,
             );
+            let sender = match statement.to_pdf() {
+                Ok(pdf) => sender.with_attachment(&statement.file_name(), PDF_CONTENT_TYPE, pdf),
+                Err(err) => {
+                    tracing::warn!("生成积分证明失败 {}: {}", student.github_login, err);
+                    sender
+                }
+            };
             sender.send().await.unwrap();
         }
     }
+
+    /// 月度计算完成后向管理员发送劳务费统计表
+    pub async fn payout_report_email(report: &PayoutReport) -> Result<(), Error> {
+        let receivers = admin_report_recipients();
+        if receivers.is_empty() {
+            return Ok(());
+        }
+        let workbook = report.to_workbook(HeaderLang::Zh)?;
+        let mut email_context = tera::Context::new();
+        email_context.insert("summary", &report.summary());
+        let subject = format!(
+            "R2CN{}年{}月劳务费统计表/R2CN Monthly Payout Report - {}-{:02}",
+            report.year, report.month, report.year, report.month
+        );
+        EmailSender::from_local_template_to_many(
+            "monthly_payout_report.mjml",
+            &subject,
+            email_context,
+            receivers,
+            vec![],
+        )
+        .with_attachment(&report.file_name("xlsx"), XLSX_CONTENT_TYPE, workbook)
+        .send()
+        .await
+    }
+}
+
+/// ADMIN_REPORT_RECIPIENTS 配置的管理员邮箱，逗号分隔
+pub fn admin_report_recipients() -> Vec<String> {
+    env::var("ADMIN_REPORT_RECIPIENTS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|email| email.trim().to_owned())
+        .filter(|email| !email.is_empty())
+        .collect()
 }
 
 pub mod util {
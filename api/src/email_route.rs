@@ -15,7 +15,7 @@ async fn announcement(
     state: State<AppState>,
     Json(json): Json<EmailAnnouncement>,
 ) -> Result<Json<CommonResult<()>>, CommonError> {
-    let res = EmailSender::notice_all_email(state, &json.temp_id).await;
+    let res = EmailSender::notice_all_email(state, &json.temp_id, json.program_id).await;
     let res = match res {
         Ok(_) => CommonResult::success(Some(())),
         Err(err) => CommonResult::failed(&err.to_string()),
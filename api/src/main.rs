@@ -2,20 +2,28 @@ mod confernece_router;
 mod email;
 mod email_route;
 mod mentor_router;
+mod metrics;
 mod model;
+mod payout_router;
+mod program_router;
+mod report;
 mod score_router;
+mod stats_router;
 mod student_router;
+mod student_sync;
 mod task_router;
 
-use std::env;
+use std::{env, sync::Arc};
 
-use axum::Router;
+use axum::{Router, middleware};
 use migration::{Migrator, MigratorTrait};
 use sea_orm::Database;
 use service::{
     Context,
+    ospp::OsppClient,
     storage::{
-        mentor_stg::MentorStorage, score_stg::ScoreStorage, student_stg::StudentStorage,
+        mentor_stg::MentorStorage, payout_stg::PayoutStorage, program_stg::ProgramStorage,
+        score_stg::ScoreStorage, stats_stg::StatsStorage, student_stg::StudentStorage,
         task_stg::TaskStorage,
     },
 };
@@ -39,9 +47,12 @@ async fn start() -> anyhow::Result<()> {
         .await
         .expect("Database connection failed");
 
+    let metrics_handle = metrics::install_recorder();
+
     Migrator::up(&conn, None).await.unwrap();
     let context = Context::new(conn.into()).await;
     let state = AppState { context };
+    student_sync::spawn(state.clone());
 
     let api_router = Router::new()
         .merge(confernece_router::routers())
@@ -49,10 +60,15 @@ async fn start() -> anyhow::Result<()> {
         .merge(student_router::routers())
         .merge(score_router::routers())
         .merge(mentor_router::routers())
+        .merge(payout_router::routers())
+        .merge(program_router::routers())
+        .merge(stats_router::routers())
         .merge(email_route::routers());
 
     let app = Router::new()
         .nest("/api/v1/", api_router)
+        .merge(metrics::routers(metrics_handle))
+        .layer(middleware::from_fn(metrics::track_http))
         .layer(CookieManagerLayer::new())
         .layer(TraceLayer::new_for_http())
         .with_state(state);
@@ -84,6 +100,22 @@ impl AppState {
     fn mentor_stg(&self) -> MentorStorage {
         self.context.services.mentor_stg.clone()
     }
+
+    fn payout_stg(&self) -> PayoutStorage {
+        self.context.services.payout_stg.clone()
+    }
+
+    fn stats_stg(&self) -> StatsStorage {
+        self.context.services.stats_stg.clone()
+    }
+
+    fn program_stg(&self) -> ProgramStorage {
+        self.context.services.program_stg.clone()
+    }
+
+    fn ospp(&self) -> Arc<dyn OsppClient> {
+        self.context.ospp()
+    }
 }
 
 pub fn main() {
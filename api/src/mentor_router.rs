@@ -5,6 +5,7 @@ use service::storage::mentor_stg::MentorRes;
 use crate::{
     AppState,
     model::mentor::{NewMentor, UpdateMentorStatusRequest},
+    program_router::find_program,
 };
 
 pub fn routers() -> Router<AppState> {
@@ -20,6 +21,7 @@ async fn new_mentor(
     state: State<AppState>,
     Json(json): Json<NewMentor>,
 ) -> Result<Json<CommonResult<MentorRes>>, CommonError> {
+    find_program(&state, json.program_id).await?;
     let active_model = json.into();
     let res = state.mentor_stg().new_mentor(active_model).await;
     let res = match res {
@@ -0,0 +1,72 @@
+use std::time::Instant;
+
+use axum::{
+    Router,
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::IntoResponse,
+    routing::get,
+};
+use common::metrics::{
+    HTTP_REQUEST_DURATION_SECONDS, HTTP_REQUESTS_TOTAL, MONTHLY_CALCULATION_DURATION_SECONDS,
+};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+
+const HTTP_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+const CALCULATION_BUCKETS: [f64; 8] = [0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 300.0];
+
+/// 安装全局 Prometheus recorder
+pub fn install_recorder() -> PrometheusHandle {
+    PrometheusBuilder::new()
+        .set_buckets_for_metric(
+            Matcher::Full(HTTP_REQUEST_DURATION_SECONDS.to_owned()),
+            &HTTP_BUCKETS,
+        )
+        .unwrap()
+        .set_buckets_for_metric(
+            Matcher::Full(MONTHLY_CALCULATION_DURATION_SECONDS.to_owned()),
+            &CALCULATION_BUCKETS,
+        )
+        .unwrap()
+        .install_recorder()
+        .expect("failed to install prometheus recorder")
+}
+
+/// `/metrics`，Prometheus 文本格式
+pub fn routers<S>(handle: PrometheusHandle) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    Router::new().route("/metrics", get(move || async move { handle.render() }))
+}
+
+/// 按路由模板统计请求数和耗时，未匹配的路由归为 unmatched 避免标签膨胀
+pub async fn track_http(req: Request, next: Next) -> impl IntoResponse {
+    let start = Instant::now();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_owned())
+        .unwrap_or_else(|| "unmatched".to_owned());
+    let method = req.method().to_string();
+
+    let response = next.run(req).await;
+
+    let status = response.status().as_u16().to_string();
+    metrics::counter!(
+        HTTP_REQUESTS_TOTAL,
+        "method" => method.clone(),
+        "route" => route.clone(),
+        "status" => status
+    )
+    .increment(1);
+    metrics::histogram!(
+        HTTP_REQUEST_DURATION_SECONDS,
+        "method" => method,
+        "route" => route
+    )
+    .record(start.elapsed().as_secs_f64());
+    response
+}
@@ -3,4 +3,6 @@ use serde::{Deserialize, Serialize};
 #[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
 pub struct EmailAnnouncement {
     pub temp_id: String,
-}
\ No newline at end of file
+    /// 只通知该项目的学生和导师，为空时通知全部
+    pub program_id: Option<i32>,
+}
@@ -1,7 +1,7 @@
 use entity::mentor;
 use sea_orm::ActiveValue::{NotSet, Set};
 use serde::{Deserialize, Serialize};
-use service::storage::mentor_stg::MentorStatus;
+use service::{model::program::DEFAULT_PROGRAM_ID, storage::mentor_stg::MentorStatus};
 
 #[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
 pub struct UpdateMentorStatusRequest {
@@ -15,6 +15,8 @@ pub struct NewMentor {
     pub email: String,
     pub github_login: String,
     pub status: String,
+    /// 导师所属项目，为空时归入默认项目
+    pub program_id: Option<i32>,
 }
 
 impl From<NewMentor> for mentor::ActiveModel {
@@ -27,6 +29,7 @@ impl From<NewMentor> for mentor::ActiveModel {
             status: Set(value.status),
             created_at: Set(chrono::Utc::now().naive_utc()),
             updated_at: Set(chrono::Utc::now().naive_utc()),
+            program_id: Set(value.program_id.unwrap_or(DEFAULT_PROGRAM_ID)),
         }
     }
 }
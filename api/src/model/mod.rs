@@ -1,6 +1,9 @@
 pub mod email;
 pub mod huawei_meeting;
 pub mod mentor;
+pub mod payout;
+pub mod program;
 pub mod score;
+pub mod stats;
 pub mod student;
 pub mod task;
@@ -0,0 +1,78 @@
+use chrono::NaiveDateTime;
+use entity::{payout_batch, payout_item};
+use serde::{Deserialize, Serialize};
+use service::model::payout::PayoutStatus;
+
+/// 发放批次按项目区分，program_id 为空时取默认项目
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PayoutMonth {
+    pub year: i32,
+    pub month: i32,
+    pub program_id: Option<i32>,
+}
+
+/// 查询项目的发放批次，program_id 为空时取默认项目
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PayoutBatchQuery {
+    pub year: i32,
+    pub month: i32,
+    pub program_id: Option<i32>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApprovePayoutRequest {
+    pub year: i32,
+    pub month: i32,
+    pub program_id: Option<i32>,
+    pub approver: String,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
+pub struct PayoutItemRes {
+    pub github_login: String,
+    pub student_name: String,
+    pub consumption_score: i32,
+    pub exchanged: i32,
+    pub status: PayoutStatus,
+    pub failure_reason: Option<String>,
+    pub paid_at: Option<NaiveDateTime>,
+}
+
+impl From<payout_item::Model> for PayoutItemRes {
+    fn from(value: payout_item::Model) -> Self {
+        Self {
+            github_login: value.github_login,
+            student_name: value.student_name,
+            consumption_score: value.consumption_score,
+            exchanged: value.exchanged,
+            status: PayoutStatus::from(value.status),
+            failure_reason: value.failure_reason,
+            paid_at: value.paid_at,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
+pub struct PayoutBatchRes {
+    pub year: i32,
+    pub month: i32,
+    pub program_id: i32,
+    pub status: PayoutStatus,
+    pub approved_by: Option<String>,
+    pub approved_at: Option<NaiveDateTime>,
+    pub items: Vec<PayoutItemRes>,
+}
+
+impl PayoutBatchRes {
+    pub fn new(batch: payout_batch::Model, items: Vec<payout_item::Model>) -> Self {
+        Self {
+            year: batch.year,
+            month: batch.month,
+            program_id: batch.program_id,
+            status: PayoutStatus::from(batch.status),
+            approved_by: batch.approved_by,
+            approved_at: batch.approved_at,
+            items: items.into_iter().map(|i| i.into()).collect(),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+use service::{model::program::ProgramSetting, ospp::ValidateStudentRes};
+
+/// 按项目过滤，program_id 为空时不区分项目
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProgramQuery {
+    pub program_id: Option<i32>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateProgramRequest {
+    pub id: i32,
+    #[serde(flatten)]
+    pub setting: ProgramSetting,
+}
+
+/// 手动登记学生，用于不通过 OSPP 校验的项目
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RegisterStudentRequest {
+    pub program_id: i32,
+    pub login: String,
+    pub student_name: String,
+    pub email: String,
+    pub contract_deadline: Option<String>,
+}
+
+impl From<RegisterStudentRequest> for ValidateStudentRes {
+    fn from(value: RegisterStudentRequest) -> Self {
+        Self {
+            success: true,
+            student_name: Some(value.student_name.trim().to_owned()),
+            contract_deadline: value.contract_deadline,
+            email: Some(value.email.trim().to_owned()),
+        }
+    }
+}
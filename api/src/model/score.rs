@@ -1,36 +1,131 @@
-use chrono::{Datelike, Utc};
-use entity::monthly_score;
-use sea_orm::{ActiveValue::NotSet, Set};
 use serde::{Deserialize, Serialize};
+use service::model::{
+    leaderboard::{LeaderboardFilter, RankBy},
+    simulation::RuleSet,
+};
+
+use crate::report::{HeaderLang, ReportFormat, payout::PayoutDataset};
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScoreMonth {
+    pub year: i32,
+    pub month: i32,
+}
+
+/// 月度积分列表，program_id 为空时返回全部项目
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MonthlyScoreQuery {
+    pub year: i32,
+    pub month: i32,
+    pub program_id: Option<i32>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportExcel {
+    pub year: i32,
+    pub month: i32,
+    pub program_id: Option<i32>,
+    #[serde(default)]
+    pub format: ReportFormat,
+    #[serde(default)]
+    pub lang: HeaderLang,
+    /// 仅 CSV 使用，选择导出积分总计或任务详情
+    #[serde(default)]
+    pub dataset: PayoutDataset,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportYearly {
+    pub year: i32,
+    pub program_id: Option<i32>,
+    #[serde(default)]
+    pub lang: HeaderLang,
+}
+
+/// 对比月默认为上一个月，阈值默认为 DEFAULT_OUTLIER_THRESHOLD
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompareQuery {
+    pub year: i32,
+    pub month: i32,
+    pub base_year: Option<i32>,
+    pub base_month: Option<i32>,
+    pub threshold: Option<f64>,
+    pub program_id: Option<i32>,
+    /// 支持 xlsx 和 json
+    #[serde(default)]
+    pub format: ReportFormat,
+    #[serde(default)]
+    pub lang: HeaderLang,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StatementQuery {
+    pub login: String,
+    pub year: i32,
+    pub month: i32,
+}
 
 #[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
-pub struct NewScore {
-    pub score: i32,
-    pub github_login: String,
-    pub student_name: String,
-    pub carryover_score: i32,
+pub struct LeaderboardQuery {
+    pub year: i32,
+    pub month: Option<i32>,
+    pub owner: Option<String>,
+    pub repo: Option<String>,
+    pub mentor: Option<String>,
+    pub program_id: Option<i32>,
+    #[serde(default)]
+    pub rank_by: RankBy,
+    pub limit: Option<usize>,
 }
 
-impl From<NewScore> for monthly_score::ActiveModel {
-    fn from(value: NewScore) -> Self {
+impl From<LeaderboardQuery> for LeaderboardFilter {
+    fn from(value: LeaderboardQuery) -> Self {
         Self {
-            id: NotSet,
-            github_login: Set(value.github_login),
-            student_name: Set(value.student_name),
-            year: Set(Utc::now().year()),
-            month: Set(Utc::now().month() as i32),
-            carryover_score: Set(value.carryover_score),
-            new_score: Set(value.score),
-            consumption_score: Set(0),
-            exchanged: Set(0),
-            create_at: Set(chrono::Utc::now().naive_utc()),
-            update_at: Set(chrono::Utc::now().naive_utc()),
+            year: value.year,
+            month: value.month,
+            owner: value.owner,
+            repo: value.repo,
+            mentor_github_login: value.mentor,
+            program_id: value.program_id,
         }
     }
 }
 
 #[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
-pub struct ExportExcel {
+pub struct RedemptionRequest {
+    pub login: String,
+    pub amount: i32,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CancelRedemption {
+    pub login: String,
+}
+
+/// 未指定候选规则时按各项目当前的规则重放
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SimulateRequest {
+    pub rule: Option<RuleSet>,
+    pub program_id: Option<i32>,
+    pub start_year: i32,
+    pub start_month: i32,
+    pub end_year: i32,
+    pub end_month: i32,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CloseMonthRequest {
+    pub year: i32,
+    pub month: i32,
+    pub program_id: Option<i32>,
+    pub closed_by: String,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReconcileRequest {
     pub year: i32,
     pub month: i32,
+    /// 为 true 时按差额写入调整，否则只返回不一致的记录
+    #[serde(default)]
+    pub repair: bool,
 }
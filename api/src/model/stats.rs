@@ -0,0 +1,24 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use service::model::stats::StatsFilter;
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StatsQuery {
+    pub start: Option<NaiveDate>,
+    pub end: Option<NaiveDate>,
+    pub owner: Option<String>,
+    pub repo: Option<String>,
+    pub program_id: Option<i32>,
+}
+
+impl From<StatsQuery> for StatsFilter {
+    fn from(value: StatsQuery) -> Self {
+        Self {
+            start: value.start,
+            end: value.end,
+            owner: value.owner,
+            repo: value.repo,
+            program_id: value.program_id,
+        }
+    }
+}
@@ -1,6 +1,134 @@
+use chrono::NaiveDate;
+use entity::{payout_batch, payout_item, student, student_contract};
 use serde::{Deserialize, Serialize};
+use service::model::{
+    payout::PayoutStatus,
+    student::{StudentFilter, StudentStatus, UpdateStudent},
+};
+
+use crate::model::task::Task;
 
 #[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SearchStuTask {
     pub login: String,
 }
+
+/// 手动登记合同起止日期，用于补录或续签
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StudentContractRequest {
+    pub login: String,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: NaiveDate,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChangeLogQuery {
+    pub login: Option<String>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StudentListQuery {
+    pub keyword: Option<String>,
+    pub status: Option<StudentStatus>,
+    pub program_id: Option<i32>,
+    pub page: Option<u64>,
+    pub page_size: Option<u64>,
+}
+
+impl From<StudentListQuery> for StudentFilter {
+    fn from(value: StudentListQuery) -> Self {
+        StudentFilter::new(
+            value.keyword,
+            value.status,
+            value.program_id,
+            value.page,
+            value.page_size,
+        )
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StudentLogin {
+    pub login: String,
+}
+
+/// 修改学生信息，为空的字段保持不变
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateStudentRequest {
+    pub login: String,
+    pub github_login: Option<String>,
+    pub student_name: Option<String>,
+    pub email: Option<String>,
+}
+
+impl From<UpdateStudentRequest> for UpdateStudent {
+    fn from(value: UpdateStudentRequest) -> Self {
+        Self {
+            github_login: value.github_login.map(|login| login.trim().to_owned()),
+            student_name: value.student_name.map(|name| name.trim().to_owned()),
+            email: value.email.map(|email| email.trim().to_owned()),
+        }
+    }
+}
+
+/// 变更学生状态，需要填写原因
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StudentStatusRequest {
+    pub login: String,
+    pub status: StudentStatus,
+    pub reason: String,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeactivateStudentRequest {
+    pub login: String,
+    pub reason: String,
+}
+
+/// 学生本人查看的基本信息和合同记录
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
+pub struct StudentProfile {
+    #[serde(flatten)]
+    pub student: student::Model,
+    pub contracts: Vec<student_contract::Model>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StudentTasks {
+    pub processing: Vec<Task>,
+    pub finished: Vec<Task>,
+}
+
+/// 学生当前可兑换的积分，以及本月已提交的兑换申请数额
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StudentBalance {
+    pub login: String,
+    pub year: i32,
+    pub month: i32,
+    pub balance: i32,
+    pub requested: Option<i32>,
+}
+
+/// 尚未到账的发放记录
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
+pub struct PendingPayout {
+    pub year: i32,
+    pub month: i32,
+    pub consumption_score: i32,
+    pub exchanged: i32,
+    pub status: PayoutStatus,
+    pub failure_reason: Option<String>,
+}
+
+impl From<(payout_batch::Model, payout_item::Model)> for PendingPayout {
+    fn from((batch, item): (payout_batch::Model, payout_item::Model)) -> Self {
+        Self {
+            year: batch.year,
+            month: batch.month,
+            consumption_score: item.consumption_score,
+            exchanged: item.exchanged,
+            status: item.status.into(),
+            failure_reason: item.failure_reason,
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use entity::{sea_orm_active_enums::TaskStatus, task};
 use sea_orm::{ActiveValue::NotSet, Set};
 use serde::{Deserialize, Serialize};
+use service::model::program::DEFAULT_PROGRAM_ID;
 
 #[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
 pub struct NewTask {
@@ -13,6 +14,8 @@ pub struct NewTask {
     pub mentor_github_login: String,
     pub github_issue_title: String,
     pub github_issue_link: String,
+    /// 任务所属项目，为空时归入默认项目
+    pub program_id: Option<i32>,
 }
 
 impl From<NewTask> for task::ActiveModel {
@@ -34,6 +37,13 @@ impl From<NewTask> for task::ActiveModel {
             update_at: Set(chrono::Utc::now().naive_utc()),
             github_issue_title: Set(value.github_issue_title),
             github_issue_link: Set(value.github_issue_link),
+            finished_at: NotSet,
+            out_of_contract: Set(false),
+            contract_override: Set(false),
+            contract_override_by: NotSet,
+            assigned_at: NotSet,
+            release_count: Set(0),
+            program_id: Set(value.program_id.unwrap_or(DEFAULT_PROGRAM_ID)),
         }
     }
 }
@@ -50,6 +60,9 @@ pub struct Task {
     pub task_status: TaskStatus,
     pub student_github_login: Option<String>,
     pub mentor_github_login: String,
+    pub out_of_contract: bool,
+    pub contract_override: bool,
+    pub program_id: i32,
 }
 
 impl From<task::Model> for Task {
@@ -65,6 +78,9 @@ impl From<task::Model> for Task {
             task_status: value.task_status,
             student_github_login: value.student_github_login,
             mentor_github_login: value.mentor_github_login,
+            out_of_contract: value.out_of_contract,
+            contract_override: value.contract_override,
+            program_id: value.program_id,
         }
     }
 }
@@ -87,3 +103,15 @@ pub struct UpdateScoreRequest {
     pub github_issue_title: String,
     pub score: i32,
 }
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContractOverrideRequest {
+    pub github_issue_id: i64,
+    pub mentor_login: String,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FinishedMonth {
+    pub year: i32,
+    pub month: i32,
+}
@@ -0,0 +1,104 @@
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+    routing::{get, post},
+};
+use common::{errors::CommonError, model::CommonResult};
+use service::model::payout::{PayoutImportSummary, parse_payout_result_csv};
+
+use crate::{
+    AppState,
+    email::EmailSender,
+    model::payout::{ApprovePayoutRequest, PayoutBatchQuery, PayoutBatchRes, PayoutMonth},
+    program_router::find_program,
+};
+
+pub fn routers() -> Router<AppState> {
+    Router::new().nest(
+        "/payout",
+        Router::new()
+            .route("/batch", get(get_batch))
+            .route("/approve", post(approve_batch))
+            .route("/import-result", post(import_result)),
+    )
+}
+
+async fn get_batch(
+    state: State<AppState>,
+    Query(params): Query<PayoutBatchQuery>,
+) -> Result<Json<CommonResult<PayoutBatchRes>>, CommonError> {
+    let program = find_program(&state, params.program_id).await?;
+    let batch = state
+        .payout_stg()
+        .get_batch(params.year, params.month, program.id)
+        .await
+        .unwrap()
+        .ok_or_else(|| {
+            CommonError::NotFound(format!("payout batch {}-{}", params.year, params.month))
+        })?;
+    let items = state.payout_stg().list_items(batch.id, None).await.unwrap();
+    Ok(Json(CommonResult::success(Some(PayoutBatchRes::new(
+        batch, items,
+    )))))
+}
+
+async fn approve_batch(
+    state: State<AppState>,
+    Json(json): Json<ApprovePayoutRequest>,
+) -> Result<Json<CommonResult<PayoutBatchRes>>, CommonError> {
+    let program = find_program(&state, json.program_id).await?;
+    let res = state
+        .payout_stg()
+        .approve_batch(json.year, json.month, program.id, &json.approver)
+        .await;
+    let batch = match res {
+        Ok(batch) => batch,
+        Err(err) => return Ok(Json(CommonResult::failed(&err.to_string()))),
+    };
+
+    // 审批通过后才向学生发送月度积分报告
+    let monthly_records = state
+        .score_stg()
+        .list_score_by_month(json.year, json.month, Some(program.id))
+        .await
+        .unwrap();
+    for model in monthly_records {
+        if model.new_score == 0 {
+            continue;
+        }
+        let student = state
+            .student_stg()
+            .get_student_by_login(&model.github_login)
+            .await
+            .unwrap();
+        let state_clone = state.clone();
+        tokio::spawn(async move {
+            EmailSender::monthly_score_email(state_clone, student, model.into()).await
+        });
+    }
+
+    let items = state.payout_stg().list_items(batch.id, None).await.unwrap();
+    Ok(Json(CommonResult::success(Some(PayoutBatchRes::new(
+        batch, items,
+    )))))
+}
+
+/// 导入银行返回的 CSV 回执，请求体为 CSV 文本
+async fn import_result(
+    state: State<AppState>,
+    Query(params): Query<PayoutMonth>,
+    body: String,
+) -> Result<Json<CommonResult<PayoutImportSummary>>, CommonError> {
+    let rows =
+        parse_payout_result_csv(&body).map_err(|err| CommonError::InvalidInput(err.to_string()))?;
+    let program = find_program(&state, params.program_id).await?;
+    let res = state
+        .payout_stg()
+        .apply_results(params.year, params.month, program.id, rows)
+        .await;
+    let res = match res {
+        Ok(summary) => CommonResult::success(Some(summary)),
+        Err(err) => CommonResult::failed(&err.to_string()),
+    };
+    Ok(Json(res))
+}
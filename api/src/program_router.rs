@@ -0,0 +1,134 @@
+use axum::{
+    Json, Router,
+    extract::State,
+    routing::{get, post},
+};
+use common::{errors::CommonError, model::CommonResult};
+use entity::{program, student};
+use service::model::{
+    program::{DEFAULT_PROGRAM_ID, ProgramSetting, ValidationSource},
+    student::is_valid_email,
+};
+
+use crate::{
+    AppState,
+    model::program::{RegisterStudentRequest, UpdateProgramRequest},
+};
+
+pub fn routers() -> Router<AppState> {
+    Router::new().nest(
+        "/program",
+        Router::new()
+            .route("/list", get(list_programs))
+            .route("/new", post(new_program))
+            .route("/update", post(update_program))
+            .route("/student", post(register_student)),
+    )
+}
+
+/// 校验项目是否存在，program_id 为空时取默认项目
+pub async fn find_program(
+    state: &AppState,
+    program_id: Option<i32>,
+) -> Result<program::Model, CommonError> {
+    let id = program_id.unwrap_or(DEFAULT_PROGRAM_ID);
+    state
+        .program_stg()
+        .get_program(id)
+        .await
+        .unwrap()
+        .ok_or_else(|| CommonError::NotFound(format!("program {}", id)))
+}
+
+async fn list_programs(
+    state: State<AppState>,
+) -> Result<Json<CommonResult<Vec<program::Model>>>, CommonError> {
+    let res = match state.program_stg().list_programs().await {
+        Ok(models) => CommonResult::success(Some(models)),
+        Err(err) => CommonResult::failed(&err.to_string()),
+    };
+    Ok(Json(res))
+}
+
+async fn new_program(
+    state: State<AppState>,
+    Json(json): Json<ProgramSetting>,
+) -> Result<Json<CommonResult<program::Model>>, CommonError> {
+    json.validate().map_err(CommonError::InvalidInput)?;
+    let res = match state.program_stg().new_program(json).await {
+        Ok(model) => CommonResult::success(Some(model)),
+        Err(err) => CommonResult::failed(&err.to_string()),
+    };
+    Ok(Json(res))
+}
+
+async fn update_program(
+    state: State<AppState>,
+    Json(json): Json<UpdateProgramRequest>,
+) -> Result<Json<CommonResult<program::Model>>, CommonError> {
+    json.setting.validate().map_err(CommonError::InvalidInput)?;
+    let res = match state
+        .program_stg()
+        .update_program(json.id, json.setting)
+        .await
+    {
+        Ok(model) => CommonResult::success(Some(model)),
+        Err(err) => CommonResult::failed(&err.to_string()),
+    };
+    Ok(Json(res))
+}
+
+/// 手动校验的项目由管理员登记学生，OSPP 项目的学生通过 /student/validate 登记
+async fn register_student(
+    state: State<AppState>,
+    Json(json): Json<RegisterStudentRequest>,
+) -> Result<Json<CommonResult<student::Model>>, CommonError> {
+    if json.login.is_empty() || json.login.chars().any(char::is_whitespace) {
+        return Err(CommonError::InvalidInput(format!(
+            "invalid login: {:?}",
+            json.login
+        )));
+    }
+    if json.student_name.trim().is_empty() {
+        return Err(CommonError::InvalidInput(
+            "student_name must not be empty".to_owned(),
+        ));
+    }
+    if !is_valid_email(json.email.trim()) {
+        return Err(CommonError::InvalidInput(format!(
+            "invalid email: {}",
+            json.email
+        )));
+    }
+    let program = state
+        .program_stg()
+        .get_program(json.program_id)
+        .await
+        .unwrap()
+        .ok_or_else(|| CommonError::NotFound(format!("program {}", json.program_id)))?;
+    if ValidationSource::from(program.validation_source) != ValidationSource::Manual {
+        return Err(CommonError::InvalidInput(format!(
+            "program {} validates students through OSPP, use /student/validate",
+            program.code
+        )));
+    }
+
+    let login = json.login.clone();
+    let stg = state.student_stg();
+    if let Some(student) = stg.get_student_by_login(&login).await.unwrap()
+        && student.program_id != program.id
+    {
+        return Err(CommonError::InvalidInput(format!(
+            "student {} already belongs to program {}",
+            login, student.program_id
+        )));
+    }
+    let res = match stg
+        .insert_or_update_student(&login, program.id, json.into())
+        .await
+    {
+        Ok(_) => CommonResult::success(stg.get_student_by_login(&login).await.unwrap()),
+        Err(err) => CommonResult::failed(&err.to_string()),
+    };
+    Ok(Json(res))
+}
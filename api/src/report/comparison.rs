@@ -0,0 +1,418 @@
+use std::collections::BTreeMap;
+
+use rust_xlsxwriter::{Workbook, XlsxError};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppState,
+    report::{
+        Cell, Column, HeaderLang,
+        payout::{PayoutReport, TaskDetailRow},
+        write_sheet,
+    },
+};
+
+/// 默认异常阈值：变化超过 2 倍(增长到 2 倍以上或减少到一半以下)
+pub const DEFAULT_OUTLIER_THRESHOLD: f64 = 2.0;
+
+const STUDENT_COLUMNS: [Column; 13] = [
+    Column::new("姓名", "Name"),
+    Column::new("GitHub ID", "GitHub ID"),
+    Column::new("对比月新增分数", "Base Points"),
+    Column::new("本月新增分数", "Points"),
+    Column::new("分数倍数", "Points Ratio"),
+    Column::new("对比月任务数", "Base Tasks"),
+    Column::new("本月任务数", "Tasks"),
+    Column::new("任务倍数", "Tasks Ratio"),
+    Column::new("对比月金额(元)", "Base Amount (CNY)"),
+    Column::new("本月金额(元)", "Amount (CNY)"),
+    Column::new("金额倍数", "Amount Ratio"),
+    Column::new("异常", "Outlier"),
+    Column::new("异常指标", "Outlier Metrics"),
+];
+
+const REPO_COLUMNS: [Column; 9] = [
+    Column::new("仓库", "Repository"),
+    Column::new("对比月任务分数", "Base Points"),
+    Column::new("本月任务分数", "Points"),
+    Column::new("分数倍数", "Points Ratio"),
+    Column::new("对比月任务数", "Base Tasks"),
+    Column::new("本月任务数", "Tasks"),
+    Column::new("任务倍数", "Tasks Ratio"),
+    Column::new("异常", "Outlier"),
+    Column::new("异常指标", "Outlier Metrics"),
+];
+
+/// 同一指标在对比月和本月的取值
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Change {
+    pub base: i64,
+    pub current: i64,
+    /// current / base，对比月为 0 时为空
+    pub ratio: Option<f64>,
+    pub outlier: bool,
+}
+
+impl Change {
+    /// 对比月为 0 时视为新增，不判定为异常
+    pub fn new(base: i64, current: i64, threshold: f64) -> Self {
+        let ratio = (base > 0).then(|| current as f64 / base as f64);
+        let outlier = ratio.is_some_and(|ratio| ratio >= threshold || ratio <= 1.0 / threshold);
+        Self {
+            base,
+            current,
+            ratio,
+            outlier,
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StudentComparison {
+    pub student_name: String,
+    pub github_login: String,
+    pub score: Change,
+    pub tasks: Change,
+    pub exchanged: Change,
+    pub outlier: bool,
+}
+
+/// 仓库维度只统计任务分数和任务数，兑换金额按学生结算无法拆分到仓库
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RepoComparison {
+    /// owner/repo
+    pub repository: String,
+    pub score: Change,
+    pub tasks: Change,
+    pub outlier: bool,
+}
+
+/// 两个月的环比报表，异常项排在前面
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ComparisonReport {
+    pub base_year: i32,
+    pub base_month: i32,
+    pub year: i32,
+    pub month: i32,
+    pub threshold: f64,
+    pub students: Vec<StudentComparison>,
+    pub repositories: Vec<RepoComparison>,
+}
+
+#[derive(Default)]
+struct StudentMonth {
+    student_name: String,
+    score: i64,
+    tasks: i64,
+    exchanged: i64,
+}
+
+fn student_months(report: &PayoutReport) -> BTreeMap<String, StudentMonth> {
+    let mut students: BTreeMap<String, StudentMonth> = BTreeMap::new();
+    for score in report.monthly_totals.iter().chain(&report.unpaid_totals) {
+        let student = students.entry(score.github_login.clone()).or_default();
+        student.student_name = score.student_name.clone();
+        student.score += score.new_score as i64;
+        student.exchanged += score.exchanged as i64;
+    }
+    for task in &report.task_details {
+        students
+            .entry(task.student_github_login.clone())
+            .or_default()
+            .tasks += 1;
+    }
+    students
+}
+
+/// 仓库当月的任务分数和任务数
+type RepoMonth = (i64, i64);
+
+fn repo_months(tasks: &[TaskDetailRow]) -> BTreeMap<String, RepoMonth> {
+    let mut repos: BTreeMap<String, RepoMonth> = BTreeMap::new();
+    for task in tasks {
+        let repo = repos.entry(task.repository.clone()).or_default();
+        repo.0 += task.score as i64;
+        repo.1 += 1;
+    }
+    repos
+}
+
+impl ComparisonReport {
+    pub async fn gather(
+        state: &AppState,
+        (base_year, base_month): (i32, i32),
+        (year, month): (i32, i32),
+        threshold: f64,
+        program_id: Option<i32>,
+    ) -> Result<Self, anyhow::Error> {
+        let base = PayoutReport::gather(state, base_year, base_month, program_id).await?;
+        let current = PayoutReport::gather(state, year, month, program_id).await?;
+        Ok(Self::build(&base, &current, threshold))
+    }
+
+    pub fn build(base: &PayoutReport, current: &PayoutReport, threshold: f64) -> Self {
+        let mut base_students = student_months(base);
+        let mut students: Vec<StudentComparison> = vec![];
+        for (login, now) in student_months(current) {
+            let before = base_students.remove(&login).unwrap_or_default();
+            students.push(Self::student(login, before, now, threshold));
+        }
+        for (login, before) in base_students {
+            students.push(Self::student(login, before, Default::default(), threshold));
+        }
+        students.sort_by(|a, b| {
+            b.outlier
+                .cmp(&a.outlier)
+                .then_with(|| a.github_login.cmp(&b.github_login))
+        });
+
+        let mut base_repos = repo_months(&base.task_details);
+        let mut pairs: Vec<(String, RepoMonth, RepoMonth)> = repo_months(&current.task_details)
+            .into_iter()
+            .map(|(repository, now)| {
+                let before = base_repos.remove(&repository).unwrap_or_default();
+                (repository, before, now)
+            })
+            .collect();
+        pairs.extend(
+            base_repos
+                .into_iter()
+                .map(|(repository, before)| (repository, before, (0, 0))),
+        );
+        let mut repositories: Vec<RepoComparison> = pairs
+            .into_iter()
+            .map(|(repository, before, now)| {
+                let score = Change::new(before.0, now.0, threshold);
+                let tasks = Change::new(before.1, now.1, threshold);
+                RepoComparison {
+                    repository,
+                    outlier: score.outlier || tasks.outlier,
+                    score,
+                    tasks,
+                }
+            })
+            .collect();
+        repositories.sort_by(|a, b| {
+            b.outlier
+                .cmp(&a.outlier)
+                .then_with(|| a.repository.cmp(&b.repository))
+        });
+
+        Self {
+            base_year: base.year,
+            base_month: base.month,
+            year: current.year,
+            month: current.month,
+            threshold,
+            students,
+            repositories,
+        }
+    }
+
+    fn student(
+        github_login: String,
+        before: StudentMonth,
+        now: StudentMonth,
+        threshold: f64,
+    ) -> StudentComparison {
+        let score = Change::new(before.score, now.score, threshold);
+        let tasks = Change::new(before.tasks, now.tasks, threshold);
+        let exchanged = Change::new(before.exchanged, now.exchanged, threshold);
+        let student_name = if now.student_name.is_empty() {
+            before.student_name
+        } else {
+            now.student_name
+        };
+        StudentComparison {
+            student_name,
+            github_login,
+            outlier: score.outlier || tasks.outlier || exchanged.outlier,
+            score,
+            tasks,
+            exchanged,
+        }
+    }
+
+    pub fn file_name(&self, extension: &str) -> String {
+        format!(
+            "开源实习-环比报表-{}年{}月-{}年{}月.{}",
+            self.base_year, self.base_month, self.year, self.month, extension
+        )
+    }
+
+    pub fn to_workbook(&self, lang: HeaderLang) -> Result<Vec<u8>, XlsxError> {
+        let mut workbook = Workbook::new();
+        let students = self
+            .students
+            .iter()
+            .map(|student| {
+                let mut cells = vec![
+                    student.student_name.as_str().into(),
+                    student.github_login.as_str().into(),
+                ];
+                cells.extend(change_cells(&student.score, Cell::Int));
+                cells.extend(change_cells(&student.tasks, Cell::Int));
+                cells.extend(change_cells(&student.exchanged, Cell::Money));
+                cells.push(outlier_cell(student.outlier, lang));
+                cells.push(
+                    outlier_metrics(
+                        [
+                            (&student.score, ("分数", "points")),
+                            (&student.tasks, ("任务", "tasks")),
+                            (&student.exchanged, ("金额", "amount")),
+                        ],
+                        lang,
+                    )
+                    .into(),
+                );
+                cells
+            })
+            .collect();
+        write_sheet(
+            &mut workbook,
+            "学生环比",
+            &STUDENT_COLUMNS,
+            lang,
+            students,
+            &[],
+        )?;
+
+        let repositories = self
+            .repositories
+            .iter()
+            .map(|repo| {
+                let mut cells = vec![repo.repository.as_str().into()];
+                cells.extend(change_cells(&repo.score, Cell::Int));
+                cells.extend(change_cells(&repo.tasks, Cell::Int));
+                cells.push(outlier_cell(repo.outlier, lang));
+                cells.push(
+                    outlier_metrics(
+                        [
+                            (&repo.score, ("分数", "points")),
+                            (&repo.tasks, ("任务", "tasks")),
+                        ],
+                        lang,
+                    )
+                    .into(),
+                );
+                cells
+            })
+            .collect();
+        write_sheet(
+            &mut workbook,
+            "仓库环比",
+            &REPO_COLUMNS,
+            lang,
+            repositories,
+            &[],
+        )?;
+        workbook.save_to_buffer()
+    }
+}
+
+fn change_cells(change: &Change, cell: fn(i64) -> Cell) -> [Cell; 3] {
+    [
+        cell(change.base),
+        cell(change.current),
+        change.ratio.map(Cell::Ratio).unwrap_or_else(|| "".into()),
+    ]
+}
+
+fn outlier_cell(outlier: bool, lang: HeaderLang) -> Cell {
+    let text = match (outlier, lang) {
+        (true, HeaderLang::Zh) => "是",
+        (true, HeaderLang::En) => "Yes",
+        (false, _) => "",
+    };
+    text.into()
+}
+
+fn outlier_metrics<const N: usize>(
+    metrics: [(&Change, (&str, &str)); N],
+    lang: HeaderLang,
+) -> String {
+    metrics
+        .into_iter()
+        .filter(|(change, _)| change.outlier)
+        .map(|(_, (zh, en))| match lang {
+            HeaderLang::Zh => zh,
+            HeaderLang::En => en,
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::report::{
+        HeaderLang,
+        payout::{MonthlyTotalRow, PayoutReport, TaskDetailRow},
+    };
+
+    use super::{Change, ComparisonReport, DEFAULT_OUTLIER_THRESHOLD};
+
+    fn report(month: i32, new_score: i32, tasks: usize) -> PayoutReport {
+        PayoutReport {
+            year: 2025,
+            month,
+            monthly_totals: vec![MonthlyTotalRow {
+                student_name: "张三".to_owned(),
+                github_login: "zhangsan".to_owned(),
+                new_score,
+                consumption_score: new_score,
+                exchanged: new_score * 50,
+                ..Default::default()
+            }],
+            unpaid_totals: vec![],
+            task_details: vec![
+                TaskDetailRow {
+                    student_github_login: "zhangsan".to_owned(),
+                    score: 10,
+                    repository: "r2cn-dev/demo".to_owned(),
+                    ..Default::default()
+                };
+                tasks
+            ],
+        }
+    }
+
+    #[test]
+    pub fn test_change_outlier() {
+        let threshold = DEFAULT_OUTLIER_THRESHOLD;
+        assert!(Change::new(10, 30, threshold).outlier);
+        assert!(Change::new(30, 10, threshold).outlier);
+        assert!(!Change::new(20, 30, threshold).outlier);
+        let new = Change::new(0, 30, threshold);
+        assert_eq!(new.ratio, None);
+        assert!(!new.outlier);
+    }
+
+    #[test]
+    pub fn test_comparison_report() {
+        let mut base = report(2, 20, 2);
+        base.monthly_totals.push(MonthlyTotalRow {
+            student_name: "李四".to_owned(),
+            github_login: "lisi".to_owned(),
+            new_score: 10,
+            ..Default::default()
+        });
+        let current = report(3, 60, 3);
+        let report = ComparisonReport::build(&base, &current, DEFAULT_OUTLIER_THRESHOLD);
+
+        assert_eq!(report.students.len(), 2);
+        let first = &report.students[0];
+        assert_eq!(first.github_login, "lisi");
+        assert_eq!(first.score.current, 0);
+        assert!(first.outlier);
+        let second = &report.students[1];
+        assert_eq!(second.score.ratio, Some(3.0));
+        assert_eq!(second.exchanged.ratio, Some(3.0));
+        assert!(!second.tasks.outlier);
+
+        let repo = &report.repositories[0];
+        assert_eq!((repo.score.base, repo.score.current), (20, 30));
+        assert!(!repo.outlier);
+
+        assert!(!report.to_workbook(HeaderLang::Zh).unwrap().is_empty());
+    }
+}
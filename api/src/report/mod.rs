@@ -0,0 +1,228 @@
+use axum::{body::Body, response::Response};
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use rust_xlsxwriter::{Format, Workbook, XlsxError, utility::column_number_to_name};
+use serde::{Deserialize, Serialize};
+
+pub mod comparison;
+pub mod payout;
+pub mod statement;
+pub mod yearly;
+
+pub const XLSX_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+pub const CSV_CONTENT_TYPE: &str = "text/csv; charset=utf-8";
+pub const JSON_CONTENT_TYPE: &str = "application/json";
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Xlsx,
+    Csv,
+    Json,
+}
+
+impl ReportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Xlsx => "xlsx",
+            ReportFormat::Csv => "csv",
+            ReportFormat::Json => "json",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ReportFormat::Xlsx => XLSX_CONTENT_TYPE,
+            ReportFormat::Csv => CSV_CONTENT_TYPE,
+            ReportFormat::Json => JSON_CONTENT_TYPE,
+        }
+    }
+}
+
+/// 报表表头语言，默认中文
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HeaderLang {
+    #[default]
+    Zh,
+    En,
+}
+
+/// 表头的中英文名称
+pub struct Column {
+    pub zh: &'static str,
+    pub en: &'static str,
+}
+
+impl Column {
+    pub const fn new(zh: &'static str, en: &'static str) -> Self {
+        Self { zh, en }
+    }
+
+    pub fn title(&self, lang: HeaderLang) -> &'static str {
+        match lang {
+            HeaderLang::Zh => self.zh,
+            HeaderLang::En => self.en,
+        }
+    }
+}
+
+pub fn headers(columns: &[Column], lang: HeaderLang) -> Vec<&'static str> {
+    columns.iter().map(|c| c.title(lang)).collect()
+}
+
+/// 工作表中的单元格
+pub enum Cell {
+    Text(String),
+    Int(i64),
+    /// 金额(元)
+    Money(i64),
+    /// 倍数，保留两位小数
+    Ratio(f64),
+}
+
+impl From<&str> for Cell {
+    fn from(value: &str) -> Self {
+        Cell::Text(value.to_owned())
+    }
+}
+
+impl From<String> for Cell {
+    fn from(value: String) -> Self {
+        Cell::Text(value)
+    }
+}
+
+impl From<i32> for Cell {
+    fn from(value: i32) -> Self {
+        Cell::Int(value as i64)
+    }
+}
+
+impl From<i64> for Cell {
+    fn from(value: i64) -> Self {
+        Cell::Int(value)
+    }
+}
+
+/// 写入一张带表头的工作表：表头加粗并冻结，数字按千分位显示；
+/// `sum_columns` 不为空且有数据行时在末尾追加合计行
+pub fn write_sheet(
+    workbook: &mut Workbook,
+    name: &str,
+    columns: &[Column],
+    lang: HeaderLang,
+    rows: Vec<Vec<Cell>>,
+    sum_columns: &[u16],
+) -> Result<(), XlsxError> {
+    let header_format = Format::new().set_bold();
+    let int_format = Format::new().set_num_format("#,##0");
+    let money_format = Format::new().set_num_format("#,##0.00");
+    let ratio_format = Format::new().set_num_format("0.00");
+    let total_int_format = int_format.clone().set_bold();
+    let total_money_format = money_format.clone().set_bold();
+
+    let sheet = workbook.add_worksheet().set_name(name)?;
+    for (col, title) in headers(columns, lang).iter().enumerate() {
+        sheet.set_column_width(col as u16, 18)?;
+        sheet.write_string_with_format(0, col as u16, *title, &header_format)?;
+    }
+    sheet.set_freeze_panes(1, 0)?;
+
+    let mut money_columns = vec![];
+    let row_count = rows.len() as u32;
+    for (idx, cells) in rows.into_iter().enumerate() {
+        let row = idx as u32 + 1;
+        for (col, cell) in cells.into_iter().enumerate() {
+            let col = col as u16;
+            match cell {
+                Cell::Text(text) => {
+                    sheet.write_string(row, col, text)?;
+                }
+                Cell::Int(number) => {
+                    sheet.write_number_with_format(row, col, number as f64, &int_format)?;
+                }
+                Cell::Money(number) => {
+                    sheet.write_number_with_format(row, col, number as f64, &money_format)?;
+                    if !money_columns.contains(&col) {
+                        money_columns.push(col);
+                    }
+                }
+                Cell::Ratio(number) => {
+                    sheet.write_number_with_format(row, col, number, &ratio_format)?;
+                }
+            }
+        }
+    }
+
+    if let Some(total_row) = total_row(sum_columns, row_count) {
+        let title = match lang {
+            HeaderLang::Zh => "合计",
+            HeaderLang::En => "Total",
+        };
+        sheet.write_string_with_format(total_row, 0, title, &header_format)?;
+        for col in sum_columns {
+            let formula = sum_formula(*col, row_count);
+            let format = if money_columns.contains(col) {
+                &total_money_format
+            } else {
+                &total_int_format
+            };
+            sheet.write_formula_with_format(total_row, *col, formula.as_str(), format)?;
+        }
+    }
+    Ok(())
+}
+
+/// 合计行所在行号，没有数据行时不写合计行，避免 SUM 引用合计单元格自身
+fn total_row(sum_columns: &[u16], row_count: u32) -> Option<u32> {
+    (!sum_columns.is_empty() && row_count > 0).then_some(row_count + 1)
+}
+
+/// 合计行中对第 2 行至最后一个数据行求和的公式
+fn sum_formula(col: u16, row_count: u32) -> String {
+    let name = column_number_to_name(col);
+    format!("=SUM({name}2:{name}{})", row_count + 1)
+}
+
+/// 以附件形式返回报表文件
+pub fn attachment_response(data: Vec<u8>, content_type: &str, file_name: &str) -> Response<Body> {
+    let encoded_filename = utf8_percent_encode(file_name, NON_ALPHANUMERIC).to_string();
+    let disposition = format!("attachment; filename*=UTF-8''{}", encoded_filename);
+    Response::builder()
+        .header("Content-Type", content_type)
+        .header("Content-Disposition", disposition)
+        .body(Body::from(data))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use rust_xlsxwriter::Workbook;
+
+    use super::{Column, HeaderLang, sum_formula, total_row, write_sheet};
+
+    #[test]
+    pub fn test_write_empty_sheet() {
+        let columns = [
+            Column::new("学生", "Student"),
+            Column::new("积分", "Points"),
+        ];
+        let mut workbook = Workbook::new();
+        write_sheet(
+            &mut workbook,
+            "empty",
+            &columns,
+            HeaderLang::Zh,
+            vec![],
+            &[1],
+        )
+        .unwrap();
+        assert!(workbook.save_to_buffer().is_ok());
+        assert_eq!(total_row(&[1], 0), None);
+        assert_eq!(total_row(&[], 3), None);
+        assert_eq!(total_row(&[1], 3), Some(4));
+        assert_eq!(sum_formula(2, 3), "=SUM(C2:C4)");
+    }
+}
@@ -0,0 +1,476 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use entity::{monthly_score, task};
+use rust_xlsxwriter::{Workbook, XlsxError};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppState,
+    report::{Cell, Column, HeaderLang, headers, write_sheet},
+};
+
+pub(super) const MONTHLY_TOTAL_COLUMNS: [Column; 9] = [
+    Column::new("姓名", "Name"),
+    Column::new("GitHub ID", "GitHub ID"),
+    Column::new("上个月结转分数", "Carryover Points"),
+    Column::new("本月新增分数", "New Points"),
+    Column::new("本月转换分数", "Redeemed Points"),
+    Column::new("金额(元)", "Amount (CNY)"),
+    Column::new("兑换规则", "Redemption Rule"),
+    Column::new("合同截止日期", "Contract End Date"),
+    Column::new("命中阶梯", "Tier"),
+];
+
+const TASK_DETAIL_COLUMNS: [Column; 6] = [
+    Column::new("学生GitHub ID", "Student GitHub ID"),
+    Column::new("导师GitHub ID", "Mentor GitHub ID"),
+    Column::new("任务标题", "Task Title"),
+    Column::new("任务链接", "Task Link"),
+    Column::new("任务分数", "Task Points"),
+    Column::new("仓库", "Repository"),
+];
+
+const SUMMARY_COLUMNS: [Column; 2] = [Column::new("统计项", "Item"), Column::new("数值", "Value")];
+
+const REPO_BREAKDOWN_COLUMNS: [Column; 3] = [
+    Column::new("仓库", "Repository"),
+    Column::new("任务数", "Tasks"),
+    Column::new("任务分数", "Task Points"),
+];
+
+const MENTOR_BREAKDOWN_COLUMNS: [Column; 3] = [
+    Column::new("导师GitHub ID", "Mentor GitHub ID"),
+    Column::new("任务数", "Tasks"),
+    Column::new("任务分数", "Task Points"),
+];
+
+/// 导出的数据集，CSV 每次只能导出其中一个
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PayoutDataset {
+    #[default]
+    Totals,
+    Tasks,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MonthlyTotalRow {
+    pub student_name: String,
+    pub github_login: String,
+    pub carryover_score: i32,
+    pub new_score: i32,
+    pub consumption_score: i32,
+    pub exchanged: i32,
+    pub score_strategy: Option<String>,
+    pub contract_end_date: Option<NaiveDate>,
+    pub tier: Option<i32>,
+}
+
+impl MonthlyTotalRow {
+    /// 兑换规则相关的三列，空值输出为空字符串
+    fn rule_columns(&self) -> [String; 3] {
+        [
+            self.score_strategy.clone().unwrap_or_default(),
+            self.contract_end_date
+                .map(|date| date.to_string())
+                .unwrap_or_default(),
+            self.tier.map(|tier| tier.to_string()).unwrap_or_default(),
+        ]
+    }
+}
+
+impl From<monthly_score::Model> for MonthlyTotalRow {
+    fn from(value: monthly_score::Model) -> Self {
+        Self {
+            student_name: value.student_name,
+            github_login: value.github_login,
+            carryover_score: value.carryover_score,
+            new_score: value.new_score,
+            consumption_score: value.consumption_score,
+            exchanged: value.exchanged,
+            score_strategy: value.score_strategy,
+            contract_end_date: value.contract_end_date,
+            tier: value.tier,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaskDetailRow {
+    pub student_github_login: String,
+    pub mentor_github_login: String,
+    pub github_issue_title: String,
+    pub github_issue_link: String,
+    pub score: i32,
+    /// owner/repo
+    pub repository: String,
+}
+
+impl From<task::Model> for TaskDetailRow {
+    fn from(value: task::Model) -> Self {
+        Self {
+            repository: format!("{}/{}", value.owner, value.repo),
+            student_github_login: value.student_github_login.unwrap_or_default(),
+            mentor_github_login: value.mentor_github_login,
+            github_issue_title: value.github_issue_title,
+            github_issue_link: value.github_issue_link,
+            score: value.score,
+        }
+    }
+}
+
+/// 按仓库或导师汇总的任务数和任务分数
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BreakdownRow {
+    pub key: String,
+    pub tasks: i64,
+    pub score: i64,
+}
+
+/// 报表汇总数据，students 包含兑换金额为 0 的学生，其人数另计入 unpaid_students
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PayoutSummary {
+    pub year: i32,
+    pub month: i32,
+    pub students: i64,
+    pub unpaid_students: i64,
+    pub new_score: i64,
+    pub consumption_score: i64,
+    pub exchanged: i64,
+    pub tasks: i64,
+    pub task_score: i64,
+}
+
+/// 月度劳务费报表：当月积分总计和当月任务详情
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PayoutReport {
+    pub year: i32,
+    pub month: i32,
+    pub monthly_totals: Vec<MonthlyTotalRow>,
+    /// 当月没有兑换金额的学生
+    pub unpaid_totals: Vec<MonthlyTotalRow>,
+    pub task_details: Vec<TaskDetailRow>,
+}
+
+impl PayoutReport {
+    /// program_id 为空时统计全部项目
+    pub async fn gather(
+        state: &AppState,
+        year: i32,
+        month: i32,
+        program_id: Option<i32>,
+    ) -> Result<Self, anyhow::Error> {
+        let (monthly_totals, unpaid_totals): (Vec<MonthlyTotalRow>, Vec<MonthlyTotalRow>) = state
+            .score_stg()
+            .list_score_by_month(year, month, program_id)
+            .await?
+            .into_iter()
+            .map(MonthlyTotalRow::from)
+            .partition(|score| score.exchanged != 0);
+        let task_details = state
+            .task_stg()
+            .search_finished_task_with_date(year, month, program_id)
+            .await?
+            .into_iter()
+            .map(|task| task.into())
+            .collect();
+        Ok(Self {
+            year,
+            month,
+            monthly_totals,
+            unpaid_totals,
+            task_details,
+        })
+    }
+
+    pub fn summary(&self) -> PayoutSummary {
+        PayoutSummary {
+            year: self.year,
+            month: self.month,
+            students: (self.monthly_totals.len() + self.unpaid_totals.len()) as i64,
+            unpaid_students: self.unpaid_totals.len() as i64,
+            // 未兑换的学生当月同样有新增积分
+            new_score: sum_by(&self.monthly_totals, |s| s.new_score)
+                + sum_by(&self.unpaid_totals, |s| s.new_score),
+            consumption_score: sum_by(&self.monthly_totals, |s| s.consumption_score),
+            exchanged: sum_by(&self.monthly_totals, |s| s.exchanged),
+            tasks: self.task_details.len() as i64,
+            task_score: sum_by(&self.task_details, |t| t.score),
+        }
+    }
+
+    pub fn repo_breakdown(&self) -> Vec<BreakdownRow> {
+        breakdown(&self.task_details, |task| &task.repository)
+    }
+
+    pub fn mentor_breakdown(&self) -> Vec<BreakdownRow> {
+        breakdown(&self.task_details, |task| &task.mentor_github_login)
+    }
+
+    pub fn file_name(&self, extension: &str) -> String {
+        format!(
+            "开源实习-人员劳务费统计表-{}年{}月.{}",
+            self.year, self.month, extension
+        )
+    }
+
+    pub fn to_workbook(&self, lang: HeaderLang) -> Result<Vec<u8>, XlsxError> {
+        let mut workbook = Workbook::new();
+
+        let summary = self.summary();
+        let items: [(&str, &str, Cell); 6] = [
+            ("学生人数", "Students", Cell::Int(summary.students)),
+            ("本月新增分数", "New Points", Cell::Int(summary.new_score)),
+            (
+                "本月转换分数",
+                "Redeemed Points",
+                Cell::Int(summary.consumption_score),
+            ),
+            ("金额(元)", "Amount (CNY)", Cell::Money(summary.exchanged)),
+            ("任务数", "Tasks", Cell::Int(summary.tasks)),
+            ("任务分数", "Task Points", Cell::Int(summary.task_score)),
+        ];
+        let rows = items
+            .into_iter()
+            .map(|(zh, en, value)| vec![Column::new(zh, en).title(lang).into(), value])
+            .collect();
+        write_sheet(&mut workbook, "汇总", &SUMMARY_COLUMNS, lang, rows, &[])?;
+
+        write_sheet(
+            &mut workbook,
+            "当月积分总计",
+            &MONTHLY_TOTAL_COLUMNS,
+            lang,
+            total_rows(&self.monthly_totals),
+            &[2, 3, 4, 5],
+        )?;
+        write_sheet(
+            &mut workbook,
+            "当月任务详情",
+            &TASK_DETAIL_COLUMNS,
+            lang,
+            self.task_details
+                .iter()
+                .map(|task| {
+                    vec![
+                        task.student_github_login.as_str().into(),
+                        task.mentor_github_login.as_str().into(),
+                        task.github_issue_title.as_str().into(),
+                        task.github_issue_link.as_str().into(),
+                        task.score.into(),
+                        task.repository.as_str().into(),
+                    ]
+                })
+                .collect(),
+            &[4],
+        )?;
+        write_sheet(
+            &mut workbook,
+            "仓库汇总",
+            &REPO_BREAKDOWN_COLUMNS,
+            lang,
+            breakdown_rows(self.repo_breakdown()),
+            &[1, 2],
+        )?;
+        write_sheet(
+            &mut workbook,
+            "导师汇总",
+            &MENTOR_BREAKDOWN_COLUMNS,
+            lang,
+            breakdown_rows(self.mentor_breakdown()),
+            &[1, 2],
+        )?;
+        write_sheet(
+            &mut workbook,
+            "未兑换学生",
+            &MONTHLY_TOTAL_COLUMNS,
+            lang,
+            total_rows(&self.unpaid_totals),
+            &[2, 3, 4],
+        )?;
+
+        workbook.save_to_buffer()
+    }
+
+    pub fn to_csv(&self, dataset: PayoutDataset, lang: HeaderLang) -> Result<Vec<u8>, csv::Error> {
+        let mut writer = csv::Writer::from_writer(vec![]);
+        match dataset {
+            PayoutDataset::Totals => {
+                writer.write_record(headers(&MONTHLY_TOTAL_COLUMNS, lang))?;
+                for score in &self.monthly_totals {
+                    let [strategy, contract_end_date, tier] = score.rule_columns();
+                    writer.write_record([
+                        score.student_name.clone(),
+                        score.github_login.clone(),
+                        score.carryover_score.to_string(),
+                        score.new_score.to_string(),
+                        score.consumption_score.to_string(),
+                        score.exchanged.to_string(),
+                        strategy,
+                        contract_end_date,
+                        tier,
+                    ])?;
+                }
+            }
+            PayoutDataset::Tasks => {
+                writer.write_record(headers(&TASK_DETAIL_COLUMNS, lang))?;
+                for task in &self.task_details {
+                    writer.write_record([
+                        task.student_github_login.clone(),
+                        task.mentor_github_login.clone(),
+                        task.github_issue_title.clone(),
+                        task.github_issue_link.clone(),
+                        task.score.to_string(),
+                        task.repository.clone(),
+                    ])?;
+                }
+            }
+        }
+        writer.flush()?;
+        Ok(writer.into_inner().unwrap_or_default())
+    }
+}
+
+fn sum_by<T>(rows: &[T], value: impl Fn(&T) -> i32) -> i64 {
+    rows.iter().map(|row| value(row) as i64).sum()
+}
+
+fn breakdown(
+    tasks: &[TaskDetailRow],
+    key: impl Fn(&TaskDetailRow) -> &String,
+) -> Vec<BreakdownRow> {
+    let mut groups: BTreeMap<&String, BreakdownRow> = BTreeMap::new();
+    for task in tasks {
+        let key = key(task);
+        let row = groups.entry(key).or_insert_with(|| BreakdownRow {
+            key: key.clone(),
+            ..Default::default()
+        });
+        row.tasks += 1;
+        row.score += task.score as i64;
+    }
+    groups.into_values().collect()
+}
+
+pub(super) fn total_rows(totals: &[MonthlyTotalRow]) -> Vec<Vec<Cell>> {
+    totals
+        .iter()
+        .map(|score| {
+            let mut cells = vec![
+                score.student_name.as_str().into(),
+                score.github_login.as_str().into(),
+                score.carryover_score.into(),
+                score.new_score.into(),
+                score.consumption_score.into(),
+                Cell::Money(score.exchanged as i64),
+            ];
+            cells.extend(score.rule_columns().map(Cell::from));
+            cells
+        })
+        .collect()
+}
+
+fn breakdown_rows(rows: Vec<BreakdownRow>) -> Vec<Vec<Cell>> {
+    rows.into_iter()
+        .map(|row| vec![row.key.into(), row.tasks.into(), row.score.into()])
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::report::HeaderLang;
+
+    use super::{MonthlyTotalRow, PayoutDataset, PayoutReport, TaskDetailRow};
+
+    fn report() -> PayoutReport {
+        PayoutReport {
+            year: 2025,
+            month: 3,
+            monthly_totals: vec![MonthlyTotalRow {
+                student_name: "张三".to_owned(),
+                github_login: "zhangsan".to_owned(),
+                carryover_score: 17,
+                new_score: 30,
+                consumption_score: 40,
+                exchanged: 2000,
+                score_strategy: Some("common".to_owned()),
+                contract_end_date: None,
+                tier: Some(40),
+            }],
+            unpaid_totals: vec![MonthlyTotalRow {
+                student_name: "李四".to_owned(),
+                github_login: "lisi".to_owned(),
+                carryover_score: 0,
+                new_score: 10,
+                consumption_score: 0,
+                exchanged: 0,
+                ..Default::default()
+            }],
+            task_details: vec![
+                TaskDetailRow {
+                    student_github_login: "zhangsan".to_owned(),
+                    mentor_github_login: "mentor".to_owned(),
+                    github_issue_title: "fix, parser".to_owned(),
+                    github_issue_link: "https://github.com/r2cn-dev/demo/issues/1".to_owned(),
+                    score: 30,
+                    repository: "r2cn-dev/demo".to_owned(),
+                },
+                TaskDetailRow {
+                    student_github_login: "lisi".to_owned(),
+                    mentor_github_login: "mentor".to_owned(),
+                    github_issue_title: "add docs".to_owned(),
+                    github_issue_link: "https://github.com/r2cn-dev/site/issues/2".to_owned(),
+                    score: 10,
+                    repository: "r2cn-dev/site".to_owned(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    pub fn test_payout_csv_headers() {
+        let report = report();
+        let csv = String::from_utf8(
+            report
+                .to_csv(PayoutDataset::Totals, HeaderLang::En)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            csv,
+            "Name,GitHub ID,Carryover Points,New Points,Redeemed Points,Amount (CNY),Redemption Rule,Contract End Date,Tier\n张三,zhangsan,17,30,40,2000,common,,40\n"
+        );
+        let csv = String::from_utf8(report.to_csv(PayoutDataset::Tasks, HeaderLang::Zh).unwrap())
+            .unwrap();
+        assert!(csv.starts_with("学生GitHub ID,导师GitHub ID,任务标题,任务链接,任务分数,仓库\n"));
+        assert!(csv.contains("\"fix, parser\""));
+    }
+
+    #[test]
+    pub fn test_payout_workbook() {
+        let data = report().to_workbook(HeaderLang::Zh).unwrap();
+        assert!(!data.is_empty());
+    }
+
+    #[test]
+    pub fn test_payout_summary() {
+        let summary = report().summary();
+        assert_eq!((summary.students, summary.unpaid_students), (2, 1));
+        assert_eq!(summary.new_score, 40);
+        assert_eq!(summary.consumption_score, 40);
+        assert_eq!(summary.exchanged, 2000);
+    }
+
+    #[test]
+    pub fn test_payout_breakdown() {
+        let report = report();
+        let repos = report.repo_breakdown();
+        assert_eq!(repos.len(), 2);
+        assert_eq!(repos[0].key, "r2cn-dev/demo");
+        assert_eq!((repos[0].tasks, repos[0].score), (1, 30));
+        let mentors = report.mentor_breakdown();
+        assert_eq!(mentors.len(), 1);
+        assert_eq!((mentors[0].tasks, mentors[0].score), (2, 40));
+    }
+}
@@ -0,0 +1,258 @@
+use std::{env, fs::File, io::BufReader, path::PathBuf};
+
+use entity::{sea_orm_active_enums::TaskStatus, student, task};
+use printpdf::{BuiltinFont, IndirectFontRef, Mm, PdfDocument, PdfDocumentReference};
+use service::model::score::ScoreDto;
+
+use crate::AppState;
+
+pub const PDF_CONTENT_TYPE: &str = "application/pdf";
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 20.0;
+const LINE_HEIGHT: f32 = 7.0;
+
+/// 学生月度积分及劳务费证明
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Statement {
+    pub student_name: String,
+    pub github_login: String,
+    pub year: i32,
+    pub month: i32,
+    pub carryover_score: i32,
+    pub new_score: i32,
+    pub consumption_score: i32,
+    pub exchanged: i32,
+    pub tasks: Vec<StatementTask>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct StatementTask {
+    pub title: String,
+    pub link: String,
+    pub score: i32,
+}
+
+impl Statement {
+    pub fn new(student: &student::Model, score: &ScoreDto, tasks: &[task::Model]) -> Self {
+        Self {
+            student_name: student.student_name.clone(),
+            github_login: student.github_login.clone(),
+            year: score.year,
+            month: score.month,
+            carryover_score: score.carryover_score,
+            new_score: score.new_score,
+            consumption_score: score.consumption_score,
+            exchanged: score.exchanged,
+            tasks: tasks
+                .iter()
+                .filter(|task| task.payable())
+                .map(|task| StatementTask {
+                    title: task.github_issue_title.clone(),
+                    link: task.github_issue_link.clone(),
+                    score: task.score,
+                })
+                .collect(),
+        }
+    }
+
+    /// 学生在该月没有积分记录时返回 None
+    pub async fn gather(
+        state: &AppState,
+        login: &str,
+        year: i32,
+        month: i32,
+    ) -> Result<Option<Self>, anyhow::Error> {
+        let Some(student) = state.student_stg().get_student_by_login(login).await? else {
+            return Ok(None);
+        };
+        let Some(score) = state.score_stg().get_score(year, month, login).await? else {
+            return Ok(None);
+        };
+        let tasks = state
+            .task_stg()
+            .get_student_tasks_with_status_in_month(
+                login,
+                TaskStatus::finish_task_status(),
+                year,
+                month,
+            )
+            .await?;
+        Ok(Some(Self::new(&student, &score.into(), &tasks)))
+    }
+
+    pub fn file_name(&self) -> String {
+        format!(
+            "R2CN-statement-{}-{}-{:02}.pdf",
+            self.github_login, self.year, self.month
+        )
+    }
+
+    /// 姓名和任务标题都不含中文时内置字体即可显示
+    fn is_ascii(&self) -> bool {
+        self.student_name.is_ascii() && self.tasks.iter().all(|task| task.title.is_ascii())
+    }
+
+    pub fn to_pdf(&self) -> Result<Vec<u8>, anyhow::Error> {
+        let title = format!("R2CN Statement {}-{:02}", self.year, self.month);
+        let (doc, page, layer) =
+            PdfDocument::new(&title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+        let (font, cjk) = load_font(&doc)?;
+        if !cjk && !self.is_ascii() {
+            return Err(anyhow::anyhow!(
+                "statement of {} contains Chinese text, set STATEMENT_FONT to a CJK font",
+                self.github_login
+            ));
+        }
+        let label = |zh: &str, en: &str| {
+            if cjk {
+                format!("{zh} / {en}")
+            } else {
+                en.to_owned()
+            }
+        };
+
+        let mut layer = doc.get_page(page).get_layer(layer);
+        let mut y = PAGE_HEIGHT - MARGIN;
+        let heading = if cjk {
+            format!(
+                "R2CN 开源实习积分证明 {}年{}月 / {}",
+                self.year, self.month, title
+            )
+        } else {
+            title.clone()
+        };
+        layer.use_text(heading, 14.0, Mm(MARGIN), Mm(y), &font);
+        y -= LINE_HEIGHT * 2.0;
+
+        let lines = [
+            (label("姓名", "Name"), self.student_name.clone()),
+            (
+                label("GitHub ID", "GitHub Login"),
+                self.github_login.clone(),
+            ),
+            (
+                label("月份", "Month"),
+                format!("{}-{:02}", self.year, self.month),
+            ),
+            (
+                label("上个月结转分数", "Carryover Points"),
+                self.carryover_score.to_string(),
+            ),
+            (
+                label("本月新增分数", "Earned Points"),
+                self.new_score.to_string(),
+            ),
+            (
+                label("本月转换分数", "Redeemed Points"),
+                self.consumption_score.to_string(),
+            ),
+            (
+                label("金额(元)", "Amount (CNY)"),
+                self.exchanged.to_string(),
+            ),
+        ];
+        for (name, value) in lines {
+            layer.use_text(name, 11.0, Mm(MARGIN), Mm(y), &font);
+            layer.use_text(value, 11.0, Mm(MARGIN + 80.0), Mm(y), &font);
+            y -= LINE_HEIGHT;
+        }
+
+        y -= LINE_HEIGHT;
+        layer.use_text(
+            label("当月完成任务", "Finished Tasks"),
+            12.0,
+            Mm(MARGIN),
+            Mm(y),
+            &font,
+        );
+        y -= LINE_HEIGHT;
+        for task in &self.tasks {
+            if y < MARGIN + LINE_HEIGHT {
+                let (page, index) = doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+                layer = doc.get_page(page).get_layer(index);
+                y = PAGE_HEIGHT - MARGIN;
+            }
+            layer.use_text(
+                format!("[{}] {}", task.score, task.title),
+                10.0,
+                Mm(MARGIN),
+                Mm(y),
+                &font,
+            );
+            y -= LINE_HEIGHT * 0.7;
+            layer.use_text(&task.link, 8.0, Mm(MARGIN + 5.0), Mm(y), &font);
+            y -= LINE_HEIGHT;
+        }
+
+        Ok(doc.save_to_bytes()?)
+    }
+}
+
+/// 优先加载 STATEMENT_FONT 或 TEMPLATE_DIR/templates/font/statement.ttf 中的中文字体，
+/// 都不可用时退回内置字体，此时只输出英文标签，内容含中文时拒绝生成
+fn load_font(doc: &PdfDocumentReference) -> Result<(IndirectFontRef, bool), anyhow::Error> {
+    if let Some(path) = font_path() {
+        match File::open(&path) {
+            Ok(file) => match doc.add_external_font(BufReader::new(file)) {
+                Ok(font) => return Ok((font, true)),
+                Err(err) => tracing::warn!("加载字体失败 {}: {}", path.display(), err),
+            },
+            Err(err) => tracing::warn!("读取字体失败 {}: {}", path.display(), err),
+        }
+    }
+    Ok((doc.add_builtin_font(BuiltinFont::Helvetica)?, false))
+}
+
+fn font_path() -> Option<PathBuf> {
+    env::var("STATEMENT_FONT")
+        .ok()
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var("TEMPLATE_DIR").ok().map(|dir| {
+                let mut path = PathBuf::from(dir);
+                path.push("templates/font/statement.ttf");
+                path
+            })
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Statement, StatementTask, font_path};
+
+    #[test]
+    pub fn test_statement_pdf() {
+        let statement = Statement {
+            student_name: "Zhang San".to_owned(),
+            github_login: "zhangsan".to_owned(),
+            year: 2025,
+            month: 3,
+            carryover_score: 17,
+            new_score: 30,
+            consumption_score: 40,
+            exchanged: 2000,
+            tasks: (0..60)
+                .map(|idx| StatementTask {
+                    title: format!("task {idx}"),
+                    link: format!("https://github.com/r2cn-dev/demo/issues/{idx}"),
+                    score: 10,
+                })
+                .collect(),
+        };
+        let pdf = statement.to_pdf().unwrap();
+        assert!(pdf.starts_with(b"%PDF"));
+        assert_eq!(statement.file_name(), "R2CN-statement-zhangsan-2025-03.pdf");
+
+        // 没有配置中文字体时不生成无法阅读的证明
+        if font_path().is_none_or(|path| !path.exists()) {
+            let statement = Statement {
+                student_name: "张三".to_owned(),
+                ..statement
+            };
+            assert!(statement.to_pdf().is_err());
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::BTreeMap;
+
+use entity::monthly_score;
+use rust_xlsxwriter::{Workbook, XlsxError};
+use serde::{Deserialize, Serialize};
+use service::model::score::ScoreDto;
+
+use crate::{
+    AppState,
+    report::{
+        Cell, Column, HeaderLang,
+        payout::{MONTHLY_TOTAL_COLUMNS, MonthlyTotalRow, TaskDetailRow, total_rows},
+        write_sheet,
+    },
+};
+
+const ANNUAL_SUMMARY_COLUMNS: [Column; 7] = [
+    Column::new("姓名", "Name"),
+    Column::new("GitHub ID", "GitHub ID"),
+    Column::new("全年新增分数", "Points Earned"),
+    Column::new("全年转换分数", "Points Redeemed"),
+    Column::new("全年金额(元)", "Amount Paid (CNY)"),
+    Column::new("完成任务数", "Tasks Finished"),
+    Column::new("年末余额", "Final Balance"),
+];
+
+/// 学生全年汇总
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnnualStudentRow {
+    pub student_name: String,
+    pub github_login: String,
+    pub new_score: i64,
+    pub consumption_score: i64,
+    pub exchanged: i64,
+    pub tasks: i64,
+    /// 当年最后一条积分记录的剩余积分
+    pub final_balance: i32,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MonthTotals {
+    pub month: i32,
+    pub totals: Vec<MonthlyTotalRow>,
+}
+
+/// 年度劳务费报表：每月一张积分总计表和学生全年汇总
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct YearlyReport {
+    pub year: i32,
+    pub months: Vec<MonthTotals>,
+    pub students: Vec<AnnualStudentRow>,
+}
+
+impl YearlyReport {
+    /// program_id 为空时统计全部项目
+    pub async fn gather(
+        state: &AppState,
+        year: i32,
+        program_id: Option<i32>,
+    ) -> Result<Self, anyhow::Error> {
+        let scores = state
+            .score_stg()
+            .list_score_between((year, 1), (year, 12), program_id)
+            .await?;
+        let mut tasks = vec![];
+        for month in 1..=12 {
+            tasks.extend(
+                state
+                    .task_stg()
+                    .search_finished_task_with_date(year, month, program_id)
+                    .await?
+                    .into_iter()
+                    .map(TaskDetailRow::from),
+            );
+        }
+        Ok(Self::build(year, scores, &tasks))
+    }
+
+    /// `scores` 需按年月升序排列
+    pub fn build(year: i32, scores: Vec<monthly_score::Model>, tasks: &[TaskDetailRow]) -> Self {
+        let mut months: Vec<MonthTotals> = (1..=12)
+            .map(|month| MonthTotals {
+                month,
+                totals: vec![],
+            })
+            .collect();
+        let mut students: BTreeMap<String, AnnualStudentRow> = BTreeMap::new();
+        for score in scores {
+            let student = students
+                .entry(score.github_login.clone())
+                .or_insert_with(|| AnnualStudentRow {
+                    github_login: score.github_login.clone(),
+                    ..Default::default()
+                });
+            student.student_name = score.student_name.clone();
+            student.new_score += score.new_score as i64;
+            student.consumption_score += score.consumption_score as i64;
+            student.exchanged += score.exchanged as i64;
+            let month = score.month;
+            student.final_balance = ScoreDto::from(score.clone()).score_balance();
+            if let Some(item) = months.get_mut(month as usize - 1) {
+                item.totals.push(score.into());
+            }
+        }
+        for task in tasks {
+            if let Some(student) = students.get_mut(&task.student_github_login) {
+                student.tasks += 1;
+            }
+        }
+        Self {
+            year,
+            months,
+            students: students.into_values().collect(),
+        }
+    }
+
+    pub fn file_name(&self, extension: &str) -> String {
+        format!("开源实习-人员劳务费统计表-{}年.{}", self.year, extension)
+    }
+
+    pub fn to_workbook(&self, lang: HeaderLang) -> Result<Vec<u8>, XlsxError> {
+        let mut workbook = Workbook::new();
+        let summary = self
+            .students
+            .iter()
+            .map(|student| {
+                vec![
+                    student.student_name.as_str().into(),
+                    student.github_login.as_str().into(),
+                    student.new_score.into(),
+                    student.consumption_score.into(),
+                    Cell::Money(student.exchanged),
+                    student.tasks.into(),
+                    student.final_balance.into(),
+                ]
+            })
+            .collect();
+        write_sheet(
+            &mut workbook,
+            "全年汇总",
+            &ANNUAL_SUMMARY_COLUMNS,
+            lang,
+            summary,
+            &[2, 3, 4, 5, 6],
+        )?;
+        for month in &self.months {
+            let name = match lang {
+                HeaderLang::Zh => format!("{}月", month.month),
+                HeaderLang::En => format!("{}-{:02}", self.year, month.month),
+            };
+            write_sheet(
+                &mut workbook,
+                &name,
+                &MONTHLY_TOTAL_COLUMNS,
+                lang,
+                total_rows(&month.totals),
+                &[2, 3, 4, 5],
+            )?;
+        }
+        workbook.save_to_buffer()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+    use entity::monthly_score;
+
+    use crate::report::{HeaderLang, payout::TaskDetailRow};
+
+    use super::YearlyReport;
+
+    fn score(month: i32, carryover: i32, new: i32, consumption: i32) -> monthly_score::Model {
+        let date = NaiveDate::from_ymd_opt(2025, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        monthly_score::Model {
+            id: month,
+            github_login: "zhangsan".to_owned(),
+            student_name: "张三".to_owned(),
+            year: 2025,
+            month,
+            carryover_score: carryover,
+            new_score: new,
+            consumption_score: consumption,
+            exchanged: consumption * 50,
+            create_at: date,
+            update_at: date,
+            score_strategy: None,
+            contract_end_date: None,
+            tier: None,
+            program_id: 1,
+        }
+    }
+
+    #[test]
+    pub fn test_yearly_report() {
+        let scores = vec![score(1, 0, 30, 0), score(3, 30, 50, 60)];
+        let task = TaskDetailRow {
+            student_github_login: "zhangsan".to_owned(),
+            ..Default::default()
+        };
+        let report = YearlyReport::build(2025, scores, &[task.clone(), task]);
+        assert_eq!(report.months.len(), 12);
+        assert_eq!(report.months[0].totals.len(), 1);
+        assert!(report.months[1].totals.is_empty());
+
+        let student = &report.students[0];
+        assert_eq!(student.new_score, 80);
+        assert_eq!(student.consumption_score, 60);
+        assert_eq!(student.exchanged, 3000);
+        assert_eq!(student.tasks, 2);
+        assert_eq!(student.final_balance, 20);
+
+        assert!(!report.to_workbook(HeaderLang::En).unwrap().is_empty());
+    }
+}
@@ -1,4 +1,7 @@
-use std::io::Cursor;
+use std::{
+    collections::{HashMap, HashSet},
+    time::Instant,
+};
 
 use axum::{
     Json, Router,
@@ -7,23 +10,63 @@ use axum::{
     response::Response,
     routing::{get, post},
 };
-use chrono::{Datelike, Utc};
-use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
-use rust_xlsxwriter::Workbook;
+use chrono::{Datelike, NaiveDate, Utc};
 use sea_orm::{Set, TryIntoModel};
 
-use common::{date::get_last_month, errors::CommonError, model::CommonResult};
-use entity::monthly_score;
-use service::model::score::{CommonScore, ScoreDto, load_score_strategy};
+use common::{
+    date::{current_year_month, get_last_month, program_today},
+    errors::CommonError,
+    metrics::record_monthly_calculation,
+    model::CommonResult,
+};
+use entity::{closed_month, monthly_score, program, redemption_request, score_adjustment};
+use service::model::{
+    leaderboard::{LeaderboardEntry, LeaderboardFilter, build_leaderboard},
+    program::program_rules,
+    reconcile::ReconcileReport,
+    score::{CommonScore, RedemptionStatus, ScoreDto, load_score_strategy},
+    simulation::{RuleSet, SimulationResult, simulate},
+};
 
-use crate::{AppState, email::EmailSender, model::score::ExportExcel};
+use crate::{
+    AppState,
+    email::EmailSender,
+    model::{
+        program::ProgramQuery,
+        score::{
+            CancelRedemption, CloseMonthRequest, CompareQuery, ExportExcel, ExportYearly,
+            LeaderboardQuery, MonthlyScoreQuery, ReconcileRequest, RedemptionRequest, ScoreMonth,
+            SimulateRequest, StatementQuery,
+        },
+    },
+    program_router::find_program,
+    report::{
+        ReportFormat, XLSX_CONTENT_TYPE, attachment_response,
+        comparison::{ComparisonReport, DEFAULT_OUTLIER_THRESHOLD},
+        payout::PayoutReport,
+        statement::{PDF_CONTENT_TYPE, Statement},
+        yearly::YearlyReport,
+    },
+};
 
 pub fn routers() -> Router<AppState> {
     Router::new().nest(
         "/score",
         Router::new()
             .route("/export-excel", get(export_excel))
-            .route("/calculate-monthly", post(calculate_bonus)),
+            .route("/export-yearly", get(export_yearly))
+            .route("/compare", get(compare_months))
+            .route("/statement", get(download_statement))
+            .route("/leaderboard", get(leaderboard))
+            .route("/calculate-monthly", post(calculate_bonus))
+            .route("/redemption-request", post(request_redemption))
+            .route("/redemption-request/cancel", post(cancel_redemption))
+            .route("/simulate", post(simulate_rule))
+            .route("/close-month", post(close_month))
+            .route("/closed-months", get(list_closed_months))
+            .route("/reconcile", post(reconcile_month))
+            .route("/adjustments", get(list_adjustments))
+            .route("/monthly", get(list_monthly_scores)),
     )
 }
 
@@ -31,127 +74,190 @@ async fn export_excel(
     state: State<AppState>,
     Query(params): Query<ExportExcel>,
 ) -> Result<Response<Body>, CommonError> {
-    let monthly_records = state
-        .score_stg()
-        .list_score_by_month(params.year, params.month)
+    let report = PayoutReport::gather(&state, params.year, params.month, params.program_id)
         .await
         .unwrap();
 
-    let mut workbook = Workbook::new();
+    let file_data = match params.format {
+        ReportFormat::Xlsx => report.to_workbook(params.lang).unwrap(),
+        ReportFormat::Csv => report.to_csv(params.dataset, params.lang).unwrap(),
+        ReportFormat::Json => serde_json::to_vec(&report).unwrap(),
+    };
+    let file_name = report.file_name(params.format.extension());
+    Ok(attachment_response(
+        file_data,
+        params.format.content_type(),
+        &file_name,
+    ))
+}
 
-    let sheet1 = workbook.add_worksheet().set_name("当月积分总计").unwrap();
+async fn export_yearly(
+    state: State<AppState>,
+    Query(params): Query<ExportYearly>,
+) -> Result<Response<Body>, CommonError> {
+    let report = YearlyReport::gather(&state, params.year, params.program_id)
+        .await
+        .unwrap();
+    let file_data = report.to_workbook(params.lang).unwrap();
+    Ok(attachment_response(
+        file_data,
+        XLSX_CONTENT_TYPE,
+        &report.file_name("xlsx"),
+    ))
+}
 
-    for col in 0..6 {
-        sheet1.set_column_width(col, 18).unwrap();
+async fn compare_months(
+    state: State<AppState>,
+    Query(params): Query<CompareQuery>,
+) -> Result<Response<Body>, CommonError> {
+    let current = NaiveDate::from_ymd_opt(params.year, params.month as u32, 1)
+        .ok_or_else(|| CommonError::InvalidInput("invalid year or month".to_owned()))?;
+    let last_month = get_last_month(current);
+    let base_year = params.base_year.unwrap_or(last_month.year());
+    let base_month = params.base_month.unwrap_or(last_month.month() as i32);
+    let threshold = params.threshold.unwrap_or(DEFAULT_OUTLIER_THRESHOLD);
+    if threshold <= 1.0 {
+        return Err(CommonError::InvalidInput(
+            "threshold must be greater than 1".to_owned(),
+        ));
     }
 
-    let mut row_idx = 1;
+    let report = ComparisonReport::gather(
+        &state,
+        (base_year, base_month),
+        (params.year, params.month),
+        threshold,
+        params.program_id,
+    )
+    .await
+    .unwrap();
+    let file_data = match params.format {
+        ReportFormat::Xlsx => report.to_workbook(params.lang).unwrap(),
+        ReportFormat::Json => serde_json::to_vec(&report).unwrap(),
+        ReportFormat::Csv => {
+            return Err(CommonError::InvalidInput(
+                "comparison report supports xlsx and json only".to_owned(),
+            ));
+        }
+    };
+    Ok(attachment_response(
+        file_data,
+        params.format.content_type(),
+        &report.file_name(params.format.extension()),
+    ))
+}
 
-    sheet1.write_string(0, 0, "姓名").unwrap();
-    sheet1.write_string(0, 1, "GitHub ID").unwrap();
-    sheet1.write_string(0, 2, "上个月结转分数").unwrap();
-    sheet1.write_string(0, 3, "本月新增分数").unwrap();
-    sheet1.write_string(0, 4, "本月转换分数").unwrap();
-    sheet1.write_string(0, 5, "金额(元)").unwrap();
+async fn download_statement(
+    state: State<AppState>,
+    Query(params): Query<StatementQuery>,
+) -> Result<Response<Body>, CommonError> {
+    let statement = Statement::gather(&state, &params.login, params.year, params.month)
+        .await
+        .unwrap()
+        .ok_or_else(|| {
+            CommonError::NotFound(format!(
+                "score of {} in {}-{}",
+                params.login, params.year, params.month
+            ))
+        })?;
+    let file_data = statement
+        .to_pdf()
+        .map_err(|err| CommonError::IO(std::io::Error::other(err.to_string())))?;
+    Ok(attachment_response(
+        file_data,
+        PDF_CONTENT_TYPE,
+        &statement.file_name(),
+    ))
+}
 
-    for score in monthly_records {
-        if score.exchanged != 0 {
-            sheet1
-                .write_string(row_idx as u32, 0, score.student_name)
-                .unwrap();
-            sheet1
-                .write_string(row_idx as u32, 1, score.github_login)
-                .unwrap();
-            sheet1
-                .write_number(row_idx as u32, 2, score.carryover_score)
-                .unwrap();
-            sheet1
-                .write_number(row_idx as u32, 3, score.new_score)
-                .unwrap();
-            sheet1
-                .write_number(row_idx as u32, 4, score.consumption_score)
-                .unwrap();
-            sheet1
-                .write_number(row_idx as u32, 5, score.exchanged)
-                .unwrap();
-            row_idx += 1;
-        }
-    }
+async fn leaderboard(
+    state: State<AppState>,
+    Query(params): Query<LeaderboardQuery>,
+) -> Result<Json<CommonResult<Vec<LeaderboardEntry>>>, CommonError> {
+    let rank_by = params.rank_by;
+    let limit = params.limit;
+    let filter: LeaderboardFilter = params.into();
 
-    let sheet2 = workbook.add_worksheet().set_name("当月任务详情").unwrap();
+    let tasks = state.task_stg().finished_task_leaderboard(&filter).await;
+    let tasks = match tasks {
+        Ok(tasks) => tasks,
+        Err(err) => return Ok(Json(CommonResult::failed(&err.to_string()))),
+    };
+    // 按仓库或导师过滤时积分只能来自任务本身，否则以月度积分表为准
+    let points = if filter.scoped_to_task() {
+        None
+    } else {
+        match state
+            .score_stg()
+            .points_leaderboard(filter.year, filter.month, filter.program_id)
+            .await
+        {
+            Ok(points) => Some(points),
+            Err(err) => return Ok(Json(CommonResult::failed(&err.to_string()))),
+        }
+    };
+    let board = build_leaderboard(points, tasks, rank_by, limit);
+    Ok(Json(CommonResult::success(Some(board))))
+}
 
-    for col in 0..5 {
-        sheet2.set_column_width(col, 18).unwrap();
+#[axum::debug_handler]
+/// program_id 不为空时只计算该项目学生的积分，为空时逐个计算全部项目，各项目的批次互不影响
+async fn calculate_bonus(
+    state: State<AppState>,
+    Query(params): Query<ProgramQuery>,
+) -> Result<Json<CommonResult<()>>, CommonError> {
+    let start = Instant::now();
+    let programs = match params.program_id {
+        Some(_) => vec![find_program(&state, params.program_id).await?],
+        None => state.program_stg().list_programs().await.unwrap(),
+    };
+    let mut failures = vec![];
+    for program in &programs {
+        let Json(result) = run_calculate_bonus(&state, program).await?;
+        if !result.message.is_empty() {
+            failures.push(format!("{}: {}", program.code, result.message));
+        }
     }
+    record_monthly_calculation(start.elapsed(), failures.is_empty());
+    if !failures.is_empty() {
+        return Ok(Json(CommonResult::failed(&failures.join("; "))));
+    }
+    Ok(Json(CommonResult::success(None)))
+}
 
-    sheet2.write_string(0, 0, "学生GitHub ID").unwrap();
-    sheet2.write_string(0, 1, "导师GitHub ID").unwrap();
-    sheet2.write_string(0, 2, "任务标题").unwrap();
-    sheet2.write_string(0, 3, "任务链接").unwrap();
-    sheet2.write_string(0, 4, "任务分数").unwrap();
-
-    let mut row_idx = 1;
+async fn run_calculate_bonus(
+    state: &AppState,
+    program: &program::Model,
+) -> Result<Json<CommonResult<()>>, CommonError> {
+    let calculate_month = get_last_month(program_today());
+    let (year, month) = (calculate_month.year(), calculate_month.month() as i32);
 
-    let finished_tasks = state
-        .task_stg()
-        .search_finished_task_with_date(params.year, params.month)
+    if state
+        .score_stg()
+        .is_month_closed(year, month, program.id)
         .await
-        .unwrap();
-
-    for task in finished_tasks {
-        sheet2
-            .write_string(
-                row_idx as u32,
-                0,
-                task.student_github_login.unwrap_or_default(),
-            )
-            .unwrap();
-        sheet2
-            .write_string(row_idx as u32, 1, task.mentor_github_login)
-            .unwrap();
-        sheet2
-            .write(row_idx as u32, 2, task.github_issue_title)
-            .unwrap();
-        sheet2
-            .write(row_idx as u32, 3, task.github_issue_link)
-            .unwrap();
-        sheet2.write_number(row_idx as u32, 4, task.score).unwrap();
-        row_idx += 1;
+        .unwrap()
+    {
+        return Ok(Json(CommonResult::failed(&format!(
+            "{}-{} has been closed",
+            year, month
+        ))));
     }
 
-    let file_name = format!(
-        "开源实习-人员劳务费统计表-{}年{}月.xlsx",
-        params.year, params.month
-    );
-
-    let encoded_filename = utf8_percent_encode(&file_name, NON_ALPHANUMERIC).to_string();
-    let disposition = format!("attachment; filename*=UTF-8''{}", encoded_filename);
-
-    let mut buffer = Cursor::new(Vec::new());
-
-    workbook.save_to_writer(&mut buffer).unwrap();
-    let file_data = buffer.into_inner();
-
-    let resp = Response::builder()
-        .header(
-            "Content-Type",
-            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
-        )
-        .header("Content-Disposition", disposition)
-        .body(Body::from(file_data))
-        .unwrap();
-    Ok(resp)
-}
-
-#[axum::debug_handler]
-async fn calculate_bonus(state: State<AppState>) -> Result<Json<CommonResult<()>>, CommonError> {
-    let now = Utc::now().naive_utc();
-    let calculate_month = get_last_month(now.into());
+    // 已审批的批次不允许重新计算
+    let batch = match state
+        .payout_stg()
+        .get_or_create_draft_batch(year, month, program.id)
+        .await
+    {
+        Ok(batch) => batch,
+        Err(err) => return Ok(Json(CommonResult::failed(&err.to_string()))),
+    };
 
     // 获取上个月全部记录
     let monthly_records = state
         .score_stg()
-        .list_score_by_month(calculate_month.year(), calculate_month.month() as i32)
+        .list_score_by_month(year, month, Some(program.id))
         .await
         .unwrap();
 
@@ -162,20 +268,56 @@ async fn calculate_bonus(state: State<AppState>) -> Result<Json<CommonResult<()>
             .get_student_by_login(&model.github_login)
             .await
             .unwrap();
-        let consume_score = {
+        let redemption = state
+            .score_stg()
+            .get_redemption_request(year, month, &model.github_login)
+            .await
+            .unwrap()
+            // 草稿批次重新计算时已应用的申请仍然有效
+            .filter(|r| RedemptionStatus::from(r.status.clone()) != RedemptionStatus::Cancelled);
+        let (consume_score, tier, strategy_name) = {
             let strategy = if let Some(student) = &student {
-                load_score_strategy(student, calculate_month)
+                load_score_strategy(student, calculate_month, Some(program))
             } else {
                 tracing::error!("Invalid Student Status:{}", model.github_login);
                 // fallback to default rule
                 Box::new(CommonScore)
             };
-            strategy.consumed_score(sum)
+            // 学生提交了兑换申请时按申请数额兑换，否则按阶梯自动兑换
+            match &redemption {
+                Some(redemption) => (
+                    strategy.requested_score(sum, redemption.amount),
+                    None,
+                    strategy.name(),
+                ),
+                None => (
+                    strategy.consumed_score(sum),
+                    strategy.tier(sum),
+                    strategy.name(),
+                ),
+            }
         };
+        if let Some(redemption) = &redemption
+            && RedemptionStatus::from(redemption.status.clone()) == RedemptionStatus::Pending
+        {
+            state
+                .score_stg()
+                .change_redemption_status(
+                    year,
+                    month,
+                    &model.github_login,
+                    RedemptionStatus::Applied,
+                )
+                .await
+                .unwrap();
+        }
         let mut a_model: monthly_score::ActiveModel = model.clone().into();
         // 更新上个月的发放情况
         a_model.consumption_score = Set(consume_score);
-        a_model.exchanged = Set(consume_score * 50);
+        a_model.exchanged = Set(consume_score * program.exchange_rate);
+        a_model.score_strategy = Set(Some(strategy_name.to_owned()));
+        a_model.contract_end_date = Set(student.as_ref().and_then(|s| s.contract_end_date));
+        a_model.tier = Set(tier);
         a_model.update_at = Set(Utc::now().naive_utc());
         state
             .score_stg()
@@ -189,12 +331,251 @@ async fn calculate_bonus(state: State<AppState>) -> Result<Json<CommonResult<()>
             .insert_or_update_carryover_score(last_month.clone())
             .await
             .unwrap();
-        if last_month.new_score != 0 {
-            let state_clone = state.clone();
+        // 写入草稿发放批次，月度积分邮件在审批通过后发送
+        state
+            .payout_stg()
+            .upsert_draft_item(batch.id, &last_month)
+            .await
+            .unwrap();
+    }
+
+    // 将劳务费统计表发送给管理员，发送失败不影响计算结果
+    match PayoutReport::gather(state, year, month, Some(program.id)).await {
+        Ok(report) => {
             tokio::spawn(async move {
-                EmailSender::monthly_score_email(state_clone, student, last_month).await
+                if let Err(err) = EmailSender::payout_report_email(&report).await {
+                    tracing::error!("劳务费统计表发送失败: {}", err);
+                }
             });
         }
+        Err(err) => tracing::error!("劳务费统计表生成失败: {}", err),
     }
     Ok(Json(CommonResult::success(None)))
 }
+
+/// 学生申请兑换本月积分，未申请的学生仍按阶梯规则自动兑换
+async fn request_redemption(
+    state: State<AppState>,
+    Json(json): Json<RedemptionRequest>,
+) -> Result<Json<CommonResult<redemption_request::Model>>, CommonError> {
+    let student = state
+        .student_stg()
+        .get_student_by_login(&json.login)
+        .await
+        .unwrap();
+    if student.is_none() {
+        return Err(CommonError::NotFound(format!("student {}", json.login)));
+    }
+    let (year, month) = current_year_month();
+    let balance = state
+        .score_stg()
+        .current_balance(year, month, &json.login)
+        .await
+        .unwrap();
+    if json.amount < 0 || json.amount > balance {
+        return Err(CommonError::InvalidInput(format!(
+            "amount must be between 0 and current balance {}",
+            balance
+        )));
+    }
+    let res = state
+        .score_stg()
+        .save_redemption_request(year, month, &json.login, json.amount)
+        .await;
+    let res = match res {
+        Ok(model) => CommonResult::success(Some(model)),
+        Err(err) => CommonResult::failed(&err.to_string()),
+    };
+    Ok(Json(res))
+}
+
+async fn cancel_redemption(
+    state: State<AppState>,
+    Json(json): Json<CancelRedemption>,
+) -> Result<Json<CommonResult<redemption_request::Model>>, CommonError> {
+    let (year, month) = current_year_month();
+    let stg = state.score_stg();
+    let existing = stg
+        .get_redemption_request(year, month, &json.login)
+        .await
+        .unwrap();
+    if let Some(existing) = existing
+        && RedemptionStatus::from(existing.status) == RedemptionStatus::Applied
+    {
+        return Ok(Json(CommonResult::failed(
+            "redemption request has already been applied",
+        )));
+    }
+    let res = stg
+        .change_redemption_status(year, month, &json.login, RedemptionStatus::Cancelled)
+        .await;
+    let res = match res {
+        Ok(model) => CommonResult::success(Some(model)),
+        Err(err) => CommonResult::failed(&err.to_string()),
+    };
+    Ok(Json(res))
+}
+
+/// 用候选规则重放历史积分，对比实际发放金额，不写入任何数据
+async fn simulate_rule(
+    state: State<AppState>,
+    Json(json): Json<SimulateRequest>,
+) -> Result<Json<CommonResult<SimulationResult>>, CommonError> {
+    if let Some(rule) = &json.rule {
+        rule.validate().map_err(CommonError::InvalidInput)?;
+    }
+    let start = (json.start_year, json.start_month);
+    let end = (json.end_year, json.end_month);
+    if start > end {
+        return Err(CommonError::InvalidInput(
+            "start month must not be later than end month".to_owned(),
+        ));
+    }
+
+    let history = state
+        .score_stg()
+        .list_score_between(start, end, json.program_id)
+        .await
+        .unwrap();
+    // 未指定候选规则时各项目按当前规则重放
+    let rules: HashMap<i32, RuleSet> = match &json.rule {
+        Some(rule) => history
+            .iter()
+            .map(|record| (record.program_id, rule.clone()))
+            .collect(),
+        None => state
+            .program_stg()
+            .list_programs()
+            .await
+            .unwrap()
+            .iter()
+            .map(|program| (program.id, program_rules(program)))
+            .collect(),
+    };
+    let logins = history
+        .iter()
+        .map(|r| r.github_login.clone())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    let redemptions = state
+        .score_stg()
+        .list_active_redemption_requests(logins)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|r| ((r.github_login, r.year, r.month), r.amount))
+        .collect();
+    let result = simulate(history, &redemptions, &rules);
+    Ok(Json(CommonResult::success(Some(result))))
+}
+
+/// 关账指定月份，只能关闭当前月份之前的月份
+async fn close_month(
+    state: State<AppState>,
+    Json(json): Json<CloseMonthRequest>,
+) -> Result<Json<CommonResult<closed_month::Model>>, CommonError> {
+    if !(1..=12).contains(&json.month) {
+        return Err(CommonError::InvalidInput("invalid month".to_owned()));
+    }
+    if (json.year, json.month) >= current_year_month() {
+        return Err(CommonError::InvalidInput(
+            "only months before the current month can be closed".to_owned(),
+        ));
+    }
+    let program = find_program(&state, json.program_id).await?;
+    let res = state
+        .score_stg()
+        .close_month(json.year, json.month, program.id, &json.closed_by)
+        .await;
+    let res = match res {
+        Ok(model) => CommonResult::success(Some(model)),
+        Err(err) => CommonResult::failed(&err.to_string()),
+    };
+    Ok(Json(res))
+}
+
+/// 核对任务分数与月度新增积分，repair 为 true 时按差额写入调整
+async fn reconcile_month(
+    state: State<AppState>,
+    Json(json): Json<ReconcileRequest>,
+) -> Result<Json<CommonResult<ReconcileReport>>, CommonError> {
+    if !(1..=12).contains(&json.month) {
+        return Err(CommonError::InvalidInput("invalid month".to_owned()));
+    }
+    let mut report = match state
+        .score_stg()
+        .reconcile_month(json.year, json.month)
+        .await
+    {
+        Ok(report) => report,
+        Err(err) => return Ok(Json(CommonResult::failed(&err.to_string()))),
+    };
+    for mismatch in &report.mismatches {
+        tracing::warn!(
+            "score mismatch of {} in {}-{}: expected {}, actual {}",
+            mismatch.github_login,
+            mismatch.year,
+            mismatch.month,
+            mismatch.expected,
+            mismatch.actual
+        );
+    }
+    if json.repair {
+        if let Err(err) = state
+            .score_stg()
+            .repair_mismatches(&report.mismatches)
+            .await
+        {
+            return Ok(Json(CommonResult::failed(&err.to_string())));
+        }
+        report.repaired = true;
+    }
+    Ok(Json(CommonResult::success(Some(report))))
+}
+
+async fn list_closed_months(
+    state: State<AppState>,
+    Query(params): Query<ProgramQuery>,
+) -> Result<Json<CommonResult<Vec<closed_month::Model>>>, CommonError> {
+    let res = match state
+        .score_stg()
+        .list_closed_months(params.program_id)
+        .await
+    {
+        Ok(models) => CommonResult::success(Some(models)),
+        Err(err) => CommonResult::failed(&err.to_string()),
+    };
+    Ok(Json(res))
+}
+
+async fn list_adjustments(
+    state: State<AppState>,
+    Query(params): Query<ScoreMonth>,
+) -> Result<Json<CommonResult<Vec<score_adjustment::Model>>>, CommonError> {
+    let res = match state
+        .score_stg()
+        .list_adjustments(params.year, params.month)
+        .await
+    {
+        Ok(models) => CommonResult::success(Some(models)),
+        Err(err) => CommonResult::failed(&err.to_string()),
+    };
+    Ok(Json(res))
+}
+
+/// 当月全部学生的积分记录，包含计算时采用的兑换规则、合同截止日期和命中阶梯
+async fn list_monthly_scores(
+    state: State<AppState>,
+    Query(params): Query<MonthlyScoreQuery>,
+) -> Result<Json<CommonResult<Vec<ScoreDto>>>, CommonError> {
+    let res = match state
+        .score_stg()
+        .list_score_by_month(params.year, params.month, params.program_id)
+        .await
+    {
+        Ok(models) => CommonResult::success(Some(models.into_iter().map(Into::into).collect())),
+        Err(err) => CommonResult::failed(&err.to_string()),
+    };
+    Ok(Json(res))
+}
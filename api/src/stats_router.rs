@@ -0,0 +1,83 @@
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+    routing::get,
+};
+use common::{errors::CommonError, model::CommonResult};
+use service::model::stats::{
+    ActiveParticipants, AssignmentStats, MonthlyTaskCount, PointsStats, StatusCount,
+};
+
+use crate::{AppState, model::stats::StatsQuery};
+
+pub fn routers() -> Router<AppState> {
+    Router::new().nest(
+        "/stats",
+        Router::new()
+            .route("/task-status", get(task_status))
+            .route("/task-monthly", get(task_monthly))
+            .route("/assignment", get(assignment))
+            .route("/participants", get(participants))
+            .route("/points", get(points)),
+    )
+}
+
+async fn task_status(
+    state: State<AppState>,
+    Query(params): Query<StatsQuery>,
+) -> Result<Json<CommonResult<Vec<StatusCount>>>, CommonError> {
+    let res = state.stats_stg().tasks_by_status(&params.into()).await;
+    let res = match res {
+        Ok(counts) => CommonResult::success(Some(counts)),
+        Err(err) => CommonResult::failed(&err.to_string()),
+    };
+    Ok(Json(res))
+}
+
+async fn task_monthly(
+    state: State<AppState>,
+    Query(params): Query<StatsQuery>,
+) -> Result<Json<CommonResult<Vec<MonthlyTaskCount>>>, CommonError> {
+    let res = state.stats_stg().monthly_task_counts(&params.into()).await;
+    let res = match res {
+        Ok(counts) => CommonResult::success(Some(counts)),
+        Err(err) => CommonResult::failed(&err.to_string()),
+    };
+    Ok(Json(res))
+}
+
+async fn assignment(
+    state: State<AppState>,
+    Query(params): Query<StatsQuery>,
+) -> Result<Json<CommonResult<AssignmentStats>>, CommonError> {
+    let res = state.stats_stg().assignment_stats(&params.into()).await;
+    let res = match res {
+        Ok(stats) => CommonResult::success(Some(stats)),
+        Err(err) => CommonResult::failed(&err.to_string()),
+    };
+    Ok(Json(res))
+}
+
+async fn participants(
+    state: State<AppState>,
+    Query(params): Query<StatsQuery>,
+) -> Result<Json<CommonResult<ActiveParticipants>>, CommonError> {
+    let res = state.stats_stg().active_participants(&params.into()).await;
+    let res = match res {
+        Ok(participants) => CommonResult::success(Some(participants)),
+        Err(err) => CommonResult::failed(&err.to_string()),
+    };
+    Ok(Json(res))
+}
+
+async fn points(
+    state: State<AppState>,
+    Query(params): Query<StatsQuery>,
+) -> Result<Json<CommonResult<PointsStats>>, CommonError> {
+    let res = state.stats_stg().points_stats(&params.into()).await;
+    let res = match res {
+        Ok(points) => CommonResult::success(Some(points)),
+        Err(err) => CommonResult::failed(&err.to_string()),
+    };
+    Ok(Json(res))
+}
@@ -1,11 +1,30 @@
-use axum::{Json, Router, extract::State, routing::post};
-use common::{errors::CommonError, model::CommonResult};
-use entity::sea_orm_active_enums::TaskStatus;
-use service::ospp::{ValidateStudent, ValidateStudentRes};
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+    routing::{get, post},
+};
+use common::{date::current_year_month, errors::CommonError, model::CommonResult};
+use entity::{sea_orm_active_enums::TaskStatus, student, student_change_log, student_contract};
+use service::{
+    model::{
+        program::{DEFAULT_PROGRAM_ID, ValidationSource},
+        score::{RedemptionStatus, ScoreDto},
+        student::{StudentPage, StudentStatus, UpdateStudent},
+        student_sync::StudentSyncSummary,
+    },
+    ospp::{OsppError, ValidateStudent, ValidateStudentRes},
+};
 
 use crate::{
     AppState,
-    model::{student::SearchStuTask, task::Task},
+    model::{
+        student::{
+            ChangeLogQuery, DeactivateStudentRequest, PendingPayout, SearchStuTask, StudentBalance,
+            StudentContractRequest, StudentListQuery, StudentLogin, StudentProfile,
+            StudentStatusRequest, StudentTasks, UpdateStudentRequest,
+        },
+        task::Task,
+    },
 };
 
 pub fn routers() -> Router<AppState> {
@@ -13,7 +32,20 @@ pub fn routers() -> Router<AppState> {
         "/student",
         Router::new()
             .route("/task", post(get_student_task))
-            .route("/validate", post(validate_student)),
+            .route("/validate", post(validate_student))
+            .route("/contract", post(record_contract))
+            .route("/sync", post(sync_students))
+            .route("/change-log", get(list_change_logs))
+            .route("/list", get(list_students))
+            .route("/detail", get(get_student))
+            .route("/update", post(update_student))
+            .route("/deactivate", post(deactivate_student))
+            .route("/status", post(change_student_status))
+            .route("/profile", get(get_profile))
+            .route("/tasks", get(list_tasks))
+            .route("/balance", get(get_balance))
+            .route("/score-history", get(list_score_history))
+            .route("/payouts", get(list_pending_payouts)),
     )
 }
 
@@ -21,19 +53,46 @@ async fn validate_student(
     state: State<AppState>,
     Json(json): Json<ValidateStudent>,
 ) -> Result<Json<CommonResult<ValidateStudentRes>>, CommonError> {
-    let res = service::ospp::validate_student(json.clone()).await;
+    // 默认项目通过 OSPP 校验，其他项目需要确认校验来源
+    let program_id = match json.program_id {
+        Some(program_id) => {
+            let program = state
+                .program_stg()
+                .get_program(program_id)
+                .await
+                .unwrap()
+                .ok_or_else(|| CommonError::NotFound(format!("program {}", program_id)))?;
+            if ValidationSource::from(program.validation_source) != ValidationSource::Ospp {
+                return Err(CommonError::InvalidInput(format!(
+                    "program {} does not validate students through OSPP, register them with /program/student",
+                    program.code
+                )));
+            }
+            program_id
+        }
+        None => DEFAULT_PROGRAM_ID,
+    };
+    let res = state.ospp().validate_student(&json.login).await;
     let res = match res {
-        Ok(data) => {
-            if data.success {
-                state
-                    .student_stg()
-                    .insert_or_update_student(&json.login, data.clone())
-                    .await
-                    .unwrap();
+        Ok(data) => match state
+            .student_stg()
+            .insert_or_update_student(&json.login, program_id, data.clone())
+            .await
+        {
+            Ok(_) => CommonResult::success(Some(data)),
+            Err(err) => {
+                tracing::error!("save student {} failed: {}", json.login, err);
+                CommonResult::failed(&err.to_string())
             }
-            CommonResult::success(Some(data))
+        },
+        // 学生不存在是正常的校验结果，返回 success: false
+        Err(OsppError::StudentNotFound(_)) => {
+            CommonResult::success(Some(ValidateStudentRes::default()))
+        }
+        Err(err @ OsppError::Unavailable(_)) => {
+            tracing::error!("validate student {} failed: {}", json.login, err);
+            CommonResult::failed(&err.to_string())
         }
-        Err(err) => CommonResult::failed(&err.to_string()),
     };
     Ok(Json(res))
 }
@@ -58,3 +117,272 @@ async fn get_student_task(
     };
     Ok(Json(res))
 }
+
+async fn record_contract(
+    state: State<AppState>,
+    Json(json): Json<StudentContractRequest>,
+) -> Result<Json<CommonResult<Vec<student_contract::Model>>>, CommonError> {
+    if let Some(start_date) = json.start_date
+        && start_date > json.end_date
+    {
+        return Err(CommonError::InvalidInput(
+            "start_date must not be later than end_date".to_owned(),
+        ));
+    }
+    let stg = state.student_stg();
+    let res = stg
+        .record_contract(&json.login, json.start_date, json.end_date)
+        .await;
+    let res = match res {
+        Ok(_) => CommonResult::success(Some(stg.list_contracts(&json.login).await.unwrap())),
+        Err(err) => CommonResult::failed(&err.to_string()),
+    };
+    Ok(Json(res))
+}
+
+/// 立即执行一轮 OSPP 同步
+async fn sync_students(
+    state: State<AppState>,
+) -> Result<Json<CommonResult<StudentSyncSummary>>, CommonError> {
+    let res = service::ospp::sync::sync_students(
+        state.ospp().as_ref(),
+        &state.student_stg(),
+        &state.program_stg(),
+    )
+    .await;
+    let res = match res {
+        Ok(summary) => CommonResult::success(Some(summary)),
+        Err(err) => CommonResult::failed(&err.to_string()),
+    };
+    Ok(Json(res))
+}
+
+async fn list_change_logs(
+    state: State<AppState>,
+    Query(params): Query<ChangeLogQuery>,
+) -> Result<Json<CommonResult<Vec<student_change_log::Model>>>, CommonError> {
+    let res = match state
+        .student_stg()
+        .list_change_logs(params.login.as_deref())
+        .await
+    {
+        Ok(models) => CommonResult::success(Some(models)),
+        Err(err) => CommonResult::failed(&err.to_string()),
+    };
+    Ok(Json(res))
+}
+
+async fn list_students(
+    state: State<AppState>,
+    Query(params): Query<StudentListQuery>,
+) -> Result<Json<CommonResult<StudentPage>>, CommonError> {
+    let res = match state.student_stg().search_students(&params.into()).await {
+        Ok(page) => CommonResult::success(Some(page)),
+        Err(err) => CommonResult::failed(&err.to_string()),
+    };
+    Ok(Json(res))
+}
+
+async fn find_student(state: &AppState, login: &str) -> Result<student::Model, CommonError> {
+    state
+        .student_stg()
+        .get_student_by_login(login)
+        .await
+        .unwrap()
+        .ok_or_else(|| CommonError::NotFound(format!("student {}", login)))
+}
+
+async fn get_student(
+    state: State<AppState>,
+    Query(params): Query<StudentLogin>,
+) -> Result<Json<CommonResult<student::Model>>, CommonError> {
+    let student = find_student(&state, &params.login).await?;
+    Ok(Json(CommonResult::success(Some(student))))
+}
+
+async fn get_profile(
+    state: State<AppState>,
+    Query(params): Query<StudentLogin>,
+) -> Result<Json<CommonResult<StudentProfile>>, CommonError> {
+    let student = find_student(&state, &params.login).await?;
+    let res = match state.student_stg().list_contracts(&params.login).await {
+        Ok(contracts) => CommonResult::success(Some(StudentProfile { student, contracts })),
+        Err(err) => CommonResult::failed(&err.to_string()),
+    };
+    Ok(Json(res))
+}
+
+/// 学生名下进行中和已完成的全部任务
+async fn list_tasks(
+    state: State<AppState>,
+    Query(params): Query<StudentLogin>,
+) -> Result<Json<CommonResult<StudentTasks>>, CommonError> {
+    find_student(&state, &params.login).await?;
+    let task_stg = state.task_stg();
+    let processing = task_stg
+        .list_student_tasks(&params.login, TaskStatus::processing_task_status())
+        .await;
+    let finished = task_stg
+        .list_student_tasks(&params.login, TaskStatus::finish_task_status())
+        .await;
+    let res = match (processing, finished) {
+        (Ok(processing), Ok(finished)) => CommonResult::success(Some(StudentTasks {
+            processing: processing.into_iter().map(|model| model.into()).collect(),
+            finished: finished.into_iter().map(|model| model.into()).collect(),
+        })),
+        (Err(err), _) | (_, Err(err)) => CommonResult::failed(&err.to_string()),
+    };
+    Ok(Json(res))
+}
+
+async fn get_balance(
+    state: State<AppState>,
+    Query(params): Query<StudentLogin>,
+) -> Result<Json<CommonResult<StudentBalance>>, CommonError> {
+    find_student(&state, &params.login).await?;
+    let (year, month) = current_year_month();
+    let score_stg = state.score_stg();
+    let balance = match score_stg.current_balance(year, month, &params.login).await {
+        Ok(balance) => balance,
+        Err(err) => return Ok(Json(CommonResult::failed(&err.to_string()))),
+    };
+    let requested = match score_stg
+        .get_redemption_request(year, month, &params.login)
+        .await
+    {
+        Ok(request) => request
+            .filter(|request| {
+                RedemptionStatus::from(request.status.clone()) == RedemptionStatus::Pending
+            })
+            .map(|request| request.amount),
+        Err(err) => return Ok(Json(CommonResult::failed(&err.to_string()))),
+    };
+    Ok(Json(CommonResult::success(Some(StudentBalance {
+        login: params.login,
+        year,
+        month,
+        balance,
+        requested,
+    }))))
+}
+
+/// 学生每月的结转、新增和兑换积分
+async fn list_score_history(
+    state: State<AppState>,
+    Query(params): Query<StudentLogin>,
+) -> Result<Json<CommonResult<Vec<ScoreDto>>>, CommonError> {
+    find_student(&state, &params.login).await?;
+    let res = match state.score_stg().list_score_by_login(&params.login).await {
+        Ok(models) => CommonResult::success(Some(models.into_iter().map(ScoreDto::from).collect())),
+        Err(err) => CommonResult::failed(&err.to_string()),
+    };
+    Ok(Json(res))
+}
+
+async fn list_pending_payouts(
+    state: State<AppState>,
+    Query(params): Query<StudentLogin>,
+) -> Result<Json<CommonResult<Vec<PendingPayout>>>, CommonError> {
+    find_student(&state, &params.login).await?;
+    let res = match state
+        .payout_stg()
+        .list_unpaid_items_by_login(&params.login)
+        .await
+    {
+        Ok(records) => {
+            CommonResult::success(Some(records.into_iter().map(PendingPayout::from).collect()))
+        }
+        Err(err) => CommonResult::failed(&err.to_string()),
+    };
+    Ok(Json(res))
+}
+
+async fn update_student(
+    state: State<AppState>,
+    Json(json): Json<UpdateStudentRequest>,
+) -> Result<Json<CommonResult<student::Model>>, CommonError> {
+    let login = json.login.clone();
+    let update: UpdateStudent = json.into();
+    update.validate().map_err(CommonError::InvalidInput)?;
+    let res = match state.student_stg().update_student(&login, update).await {
+        Ok(model) => CommonResult::success(Some(model)),
+        Err(err) => CommonResult::failed(&err.to_string()),
+    };
+    Ok(Json(res))
+}
+
+/// 停用即暂停实习，之后可恢复为 active
+async fn deactivate_student(
+    state: State<AppState>,
+    Json(json): Json<DeactivateStudentRequest>,
+) -> Result<Json<CommonResult<student::Model>>, CommonError> {
+    change_student_status(
+        state,
+        Json(StudentStatusRequest {
+            login: json.login,
+            status: StudentStatus::Paused,
+            reason: json.reason,
+        }),
+    )
+    .await
+}
+
+async fn change_student_status(
+    state: State<AppState>,
+    Json(json): Json<StudentStatusRequest>,
+) -> Result<Json<CommonResult<student::Model>>, CommonError> {
+    let reason = json.reason.trim();
+    if reason.is_empty() {
+        return Err(CommonError::InvalidInput("reason is required".to_owned()));
+    }
+    let res = state
+        .student_stg()
+        .change_status(&json.login, json.status, reason)
+        .await;
+    let res = match res {
+        Ok(model) => CommonResult::success(Some(model)),
+        Err(err) => CommonResult::failed(&err.to_string()),
+    };
+    Ok(Json(res))
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use axum::{Json, extract::State};
+    use sea_orm::DatabaseConnection;
+    use service::{
+        Context,
+        ospp::{ValidateStudent, fake::FakeOsppClient},
+    };
+
+    use crate::AppState;
+
+    use super::validate_student;
+
+    #[tokio::test]
+    pub async fn test_validate_student_offline() {
+        let ospp = Arc::new(FakeOsppClient::new());
+        let context =
+            Context::with_ospp(Arc::new(DatabaseConnection::Disconnected), ospp.clone()).await;
+        let state = AppState { context };
+        let request = || {
+            Json(ValidateStudent {
+                login: "zhangsan".to_owned(),
+                program_id: None,
+            })
+        };
+
+        let Json(res) = validate_student(State(state.clone()), request())
+            .await
+            .unwrap();
+        assert!(res.message.is_empty());
+        assert!(!res.data.unwrap().success);
+
+        ospp.set_unavailable(true);
+        let Json(res) = validate_student(State(state), request()).await.unwrap();
+        assert!(res.data.is_none());
+        assert!(res.message.contains("OSPP unavailable"));
+    }
+}
@@ -0,0 +1,39 @@
+use std::{env, time::Duration};
+
+use service::ospp::sync::sync_students;
+use tokio::time::{Instant, interval_at};
+
+use crate::AppState;
+
+const DEFAULT_SYNC_INTERVAL_HOURS: u64 = 24;
+/// 启动后首次同步的延迟，避免频繁重启时一直等不到下一个周期
+const STARTUP_DELAY: Duration = Duration::from_secs(60);
+
+/// 启动后先同步一次，之后按 STUDENT_SYNC_INTERVAL_HOURS 定时向 OSPP 重新校验学生信息，设为 0 时不启动
+pub fn spawn(state: AppState) {
+    let hours = env::var("STUDENT_SYNC_INTERVAL_HOURS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_SYNC_INTERVAL_HOURS);
+    if hours == 0 {
+        tracing::info!("student sync is disabled");
+        return;
+    }
+    let period = Duration::from_secs(hours * 3600);
+    tokio::spawn(async move {
+        let mut ticker = interval_at(Instant::now() + STARTUP_DELAY, period);
+        loop {
+            ticker.tick().await;
+            match sync_students(
+                state.ospp().as_ref(),
+                &state.student_stg(),
+                &state.program_stg(),
+            )
+            .await
+            {
+                Ok(summary) => tracing::info!("student sync finished: {:?}", summary),
+                Err(err) => tracing::error!("student sync failed: {}", err),
+            }
+        }
+    });
+}
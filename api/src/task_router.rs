@@ -1,23 +1,27 @@
+use std::env;
+
 use axum::{
     Json, Router,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     routing::{get, post},
 };
-use chrono::{Datelike, Utc};
-use common::{errors::CommonError, model::CommonResult};
-use entity::{monthly_score, sea_orm_active_enums::TaskStatus, task};
-use sea_orm::{Set, TryIntoModel};
-use service::model::score::ScoreDto;
+use common::{date::program_today, errors::CommonError, model::CommonResult};
+use entity::{sea_orm_active_enums::TaskStatus, task};
+use service::model::{program::check_same_program, student::check_assignable};
 
 use crate::{
     AppState,
     email::EmailSender,
-    model::{
-        score::NewScore,
-        task::{CommandRequest, NewTask, SearchTask, Task, UpdateScoreRequest},
+    model::task::{
+        CommandRequest, ContractOverrideRequest, FinishedMonth, NewTask, SearchTask, Task,
+        UpdateScoreRequest,
     },
+    program_router::find_program,
 };
 
+/// 认领任务时合同至少需要剩余的天数，可通过 ASSIGN_MIN_CONTRACT_DAYS 配置
+const DEFAULT_ASSIGN_MIN_CONTRACT_DAYS: u64 = 14;
+
 pub fn routers() -> Router<AppState> {
     Router::new().nest(
         "/task",
@@ -31,7 +35,9 @@ pub fn routers() -> Router<AppState> {
             .route("/release", post(release_task))
             .route("/request-complete", post(request_complete))
             .route("/intern-done", post(intern_done))
-            .route("/intern-close", post(intern_close)),
+            .route("/intern-close", post(intern_close))
+            .route("/contract-override", post(contract_override))
+            .route("/out-of-contract", get(search_out_of_contract)),
     )
 }
 
@@ -39,6 +45,7 @@ async fn new_task(
     state: State<AppState>,
     Json(json): Json<NewTask>,
 ) -> Result<Json<CommonResult<Task>>, CommonError> {
+    find_program(&state, json.program_id).await?;
     let active_model = json.into();
     let res = state.task_stg().new_task(active_model).await.unwrap();
     Ok(Json(CommonResult::success(Some(res.into()))))
@@ -48,12 +55,24 @@ async fn update_task_score(
     state: State<AppState>,
     Json(json): Json<UpdateScoreRequest>,
 ) -> Result<Json<CommonResult<bool>>, CommonError> {
+    let old_task = state
+        .task_stg()
+        .search_task_with_issue_id(json.github_issue_id)
+        .await
+        .unwrap();
     let res = state
         .task_stg()
         .update_score(json.github_issue_id, json.github_issue_title, json.score)
         .await;
     let res = match res {
-        Ok(_) => CommonResult::success(Some(true)),
+        Ok(task) => {
+            // 已完成任务的分数变动同步到完成月份，该月已结算或关账时计入当前月份
+            let delta = task.score - old_task.map(|t| t.score).unwrap_or(task.score);
+            if delta != 0 && task.payable() {
+                add_task_score(&state, &task, delta, "task score updated").await;
+            }
+            CommonResult::success(Some(true))
+        }
         Err(err) => CommonResult::failed(&err.to_string()),
     };
     Ok(Json(res))
@@ -102,9 +121,24 @@ async fn request_assign(
     state: State<AppState>,
     Json(json): Json<CommandRequest>,
 ) -> Result<Json<CommonResult<bool>>, CommonError> {
+    let Some(login) = json.student_login else {
+        return Err(CommonError::InvalidInput(
+            "student_login is required".to_owned(),
+        ));
+    };
+    let task = state
+        .task_stg()
+        .search_task_with_issue_id(json.github_issue_id)
+        .await
+        .unwrap()
+        .ok_or_else(|| CommonError::NotFound(format!("task {}", json.github_issue_id)))?;
+    if let Err(message) = check_student_for_task(&state, &login, &task).await {
+        return Ok(Json(CommonResult::failed(&message)));
+    }
+
     let res = state
         .task_stg()
-        .request_assign(json.github_issue_id, json.student_login.unwrap())
+        .request_assign(json.github_issue_id, login)
         .await;
 
     let res = match res {
@@ -114,10 +148,53 @@ async fn request_assign(
     Ok(Json(res))
 }
 
+/// 检查学生的登记状态、合同以及所属项目能否认领该任务
+async fn check_student_for_task(
+    state: &AppState,
+    login: &str,
+    task: &task::Model,
+) -> Result<(), String> {
+    let student_stg = state.student_stg();
+    let (student, contracts) = match (
+        student_stg.get_student_by_login(login).await,
+        student_stg.list_contracts(login).await,
+    ) {
+        (Ok(student), Ok(contracts)) => (student, contracts),
+        (Err(err), _) | (_, Err(err)) => return Err(err.to_string()),
+    };
+    let min_days = env::var("ASSIGN_MIN_CONTRACT_DAYS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_ASSIGN_MIN_CONTRACT_DAYS);
+    check_assignable(
+        login,
+        student.as_ref(),
+        &contracts,
+        program_today(),
+        min_days,
+    )?;
+    match &student {
+        Some(student) => check_same_program(student, task),
+        None => Ok(()),
+    }
+}
+
 async fn intern_approve(
     state: State<AppState>,
     Json(json): Json<CommandRequest>,
 ) -> Result<Json<CommonResult<bool>>, CommonError> {
+    // 申请到审批之间学生状态或合同可能已变化，审批时再检查一次
+    let task = state
+        .task_stg()
+        .search_task_with_issue_id(json.github_issue_id)
+        .await
+        .unwrap()
+        .ok_or_else(|| CommonError::NotFound(format!("task {}", json.github_issue_id)))?;
+    if let Some(login) = &task.student_github_login
+        && let Err(message) = check_student_for_task(&state, login, &task).await
+    {
+        return Ok(Json(CommonResult::failed(&message)));
+    }
     let res = state.task_stg().intern_approve(json.github_issue_id).await;
 
     let res = match res {
@@ -176,56 +253,98 @@ async fn intern_done(
         .intern_done(json.github_issue_id)
         .await
         .unwrap();
-    let score_stg = state.score_stg();
-    let date = Utc::now();
     let student_login = task.student_github_login.clone().unwrap();
-    let current_score = score_stg
-        .get_score(date.year(), date.month() as i32, &student_login)
+
+    // 合同期外完成的任务不计入积分，需要导师确认后才会计入
+    let in_contract = state
+        .student_stg()
+        .is_within_contract(&student_login, program_today())
         .await
         .unwrap();
-    let balance = if let Some(score) = current_score {
-        let sum_score = score.new_score + task.score;
-        let mut a_model: monthly_score::ActiveModel = score.clone().into();
-        a_model.new_score = Set(sum_score);
-        score_stg.update_score(a_model.clone()).await.unwrap();
-        let score_dto: ScoreDto = a_model.try_into_model().unwrap().into();
-        score_dto.score_balance()
-    } else {
-        let student = state
-            .student_stg()
-            .get_student_by_login(&student_login)
-            .await
-            .unwrap();
-        let mut new_score = NewScore {
-            score: task.score,
-            github_login: student_login.clone(),
-            student_name: String::new(),
-            carryover_score: 0,
-        };
-        if let Some(student) = student {
-            new_score.student_name = student.student_name;
-        };
-        let last_score = score_stg
-            .get_latest_score_by_login(&student_login)
+    if !in_contract {
+        tracing::warn!(
+            "task {} finished out of contract by {}",
+            task.github_issue_id,
+            student_login
+        );
+        let task = state
+            .task_stg()
+            .mark_out_of_contract(json.github_issue_id)
             .await
             .unwrap();
-        if let Some(last_score) = last_score {
-            let last_score: ScoreDto = last_score.into();
-            new_score.carryover_score = last_score.score_balance();
-        }
-        score_stg
-            .insert_score(new_score.clone().into())
-            .await
-            .unwrap();
-        new_score.carryover_score + new_score.score
-    };
+        return Ok(Json(CommonResult::success(Some(task))));
+    }
 
+    let balance = add_task_score(&state, &task, task.score, "task finished").await;
     let moved_task = task.clone();
     tokio::spawn(async move { EmailSender::complete_email(state, moved_task, balance).await });
 
     Ok(Json(CommonResult::success(Some(task))))
 }
 
+async fn contract_override(
+    state: State<AppState>,
+    Json(json): Json<ContractOverrideRequest>,
+) -> Result<Json<CommonResult<Task>>, CommonError> {
+    let res = state
+        .task_stg()
+        .contract_override(json.github_issue_id, &json.mentor_login)
+        .await;
+    let task = match res {
+        Ok(task) => task,
+        Err(err) => return Ok(Json(CommonResult::failed(&err.to_string()))),
+    };
+    add_task_score(&state, &task, task.score, "contract override").await;
+    Ok(Json(CommonResult::success(Some(task.into()))))
+}
+
+async fn search_out_of_contract(
+    state: State<AppState>,
+    Query(params): Query<FinishedMonth>,
+) -> Result<Json<CommonResult<Vec<Task>>>, CommonError> {
+    let res = state
+        .task_stg()
+        .search_out_of_contract_task(params.year, params.month)
+        .await;
+    let res = match res {
+        Ok(model) => {
+            let data = model.into_iter().map(|model| model.into()).collect();
+            CommonResult::success(Some(data))
+        }
+        Err(err) => CommonResult::failed(&err.to_string()),
+    };
+    Ok(Json(res))
+}
+
+/// 将已完成任务的分数计入完成月份，返回学生当前积分余额
+async fn add_task_score(
+    state: &State<AppState>,
+    task: &task::Model,
+    score: i32,
+    reason: &str,
+) -> i32 {
+    let student_login = task.student_github_login.clone().unwrap();
+    let student_name = state
+        .student_stg()
+        .get_student_by_login(&student_login)
+        .await
+        .unwrap()
+        .map(|student| student.student_name)
+        .unwrap_or_default();
+    state
+        .score_stg()
+        .add_new_score(
+            task.finish_year.unwrap(),
+            task.finish_month.unwrap(),
+            &student_login,
+            &student_name,
+            score,
+            &format!("{}: {}", reason, task.github_issue_link),
+        )
+        .await
+        .unwrap()
+}
+
 async fn intern_close(
     state: State<AppState>,
     Json(json): Json<CommandRequest>,
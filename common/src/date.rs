@@ -1,4 +1,50 @@
-use chrono::{Datelike, NaiveDate};
+use std::{env, sync::OnceLock};
+
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// 项目默认时区，任务完成月份和积分结算都以该时区的自然月为准
+pub const DEFAULT_PROGRAM_TIMEZONE: Tz = chrono_tz::Asia::Shanghai;
+
+static PROGRAM_TIMEZONE: OnceLock<Tz> = OnceLock::new();
+
+/// 读取 PROGRAM_TIMEZONE 配置，未配置或无法识别时使用 Asia/Shanghai
+pub fn program_timezone() -> Tz {
+    *PROGRAM_TIMEZONE.get_or_init(|| match env::var("PROGRAM_TIMEZONE") {
+        Ok(name) if !name.trim().is_empty() => name.trim().parse().unwrap_or_else(|_| {
+            tracing::warn!(
+                "invalid PROGRAM_TIMEZONE {}, fallback to Asia/Shanghai",
+                name
+            );
+            DEFAULT_PROGRAM_TIMEZONE
+        }),
+        _ => DEFAULT_PROGRAM_TIMEZONE,
+    })
+}
+
+/// 项目时区下的当前日期
+pub fn program_today() -> NaiveDate {
+    Utc::now().with_timezone(&program_timezone()).date_naive()
+}
+
+/// 项目时区下当前所在的年月
+pub fn current_year_month() -> (i32, i32) {
+    year_month_in(Utc::now(), program_timezone())
+}
+
+/// 将 UTC 时间换算为指定时区下的年月
+pub fn year_month_in(time: DateTime<Utc>, tz: Tz) -> (i32, i32) {
+    let local = time.with_timezone(&tz);
+    (local.year(), local.month() as i32)
+}
+
+/// 指定时区下某日零点对应的 UTC 时间
+pub fn local_midnight_utc(date: NaiveDate, tz: Tz) -> NaiveDateTime {
+    tz.from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+        .earliest()
+        .unwrap()
+        .naive_utc()
+}
 
 pub fn get_last_month(date: NaiveDate) -> NaiveDate {
     let (year, month) = if date.month() == 1 {
@@ -11,11 +57,21 @@ pub fn get_last_month(date: NaiveDate) -> NaiveDate {
     NaiveDate::from_ymd_opt(year, month, 1).unwrap()
 }
 
+pub fn get_next_month(date: NaiveDate) -> NaiveDate {
+    let (year, month) = if date.month() == 12 {
+        (date.year() + 1, 1)
+    } else {
+        (date.year(), date.month() + 1)
+    };
+
+    NaiveDate::from_ymd_opt(year, month, 1).unwrap()
+}
+
 #[cfg(test)]
 mod test {
-    use chrono::NaiveDate;
+    use chrono::{NaiveDate, TimeZone, Utc};
 
-    use crate::date::get_last_month;
+    use crate::date::{DEFAULT_PROGRAM_TIMEZONE, get_last_month, get_next_month, year_month_in};
 
     #[test]
     pub fn test_get_last_month() {
@@ -26,4 +82,32 @@ mod test {
         let test2 = get_last_month(date2);
         assert_eq!(test2, NaiveDate::from_ymd_opt(2024, 9, 1).unwrap());
     }
+
+    #[test]
+    pub fn test_get_next_month() {
+        let date = NaiveDate::from_ymd_opt(2024, 12, 31).unwrap();
+        assert_eq!(
+            get_next_month(date),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()
+        );
+        let date = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        assert_eq!(
+            get_next_month(date),
+            NaiveDate::from_ymd_opt(2024, 3, 1).unwrap()
+        );
+    }
+
+    #[test]
+    pub fn test_year_month_at_month_end() {
+        let tz = DEFAULT_PROGRAM_TIMEZONE;
+        // 北京时间 3月1日 07:00，UTC 仍是 2月28日
+        let time = Utc.with_ymd_and_hms(2025, 2, 28, 23, 0, 0).unwrap();
+        assert_eq!(year_month_in(time, tz), (2025, 3));
+        // 北京时间 1月31日 23:59
+        let time = Utc.with_ymd_and_hms(2025, 1, 31, 15, 59, 59).unwrap();
+        assert_eq!(year_month_in(time, tz), (2025, 1));
+        // 跨年：北京时间 2025年1月1日 00:00
+        let time = Utc.with_ymd_and_hms(2024, 12, 31, 16, 0, 0).unwrap();
+        assert_eq!(year_month_in(time, tz), (2025, 1));
+    }
 }
@@ -1,3 +1,4 @@
 pub mod date;
 pub mod errors;
+pub mod metrics;
 pub mod model;
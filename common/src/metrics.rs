@@ -0,0 +1,44 @@
+//! 业务指标名称及记录函数，由 api 中安装的 Prometheus recorder 导出
+
+use std::time::Duration;
+
+pub const HTTP_REQUESTS_TOTAL: &str = "http_requests_total";
+pub const HTTP_REQUEST_DURATION_SECONDS: &str = "http_request_duration_seconds";
+pub const TASK_TRANSITIONS_TOTAL: &str = "task_transitions_total";
+pub const EMAILS_SENT_TOTAL: &str = "emails_sent_total";
+pub const EXTERNAL_API_CALLS_TOTAL: &str = "external_api_calls_total";
+pub const MONTHLY_CALCULATION_DURATION_SECONDS: &str = "monthly_calculation_duration_seconds";
+
+/// 任务进入新状态
+pub fn record_task_transition(status: String) {
+    metrics::counter!(TASK_TRANSITIONS_TOTAL, "status" => status).increment(1);
+}
+
+/// 邮件发送结果，template 为本地模板名或 ZeptoMail 模板 ID
+pub fn record_email(template: &str, success: bool) {
+    let outcome = if success { "success" } else { "failure" };
+    metrics::counter!(
+        EMAILS_SENT_TOTAL,
+        "template" => template.to_owned(),
+        "outcome" => outcome
+    )
+    .increment(1);
+}
+
+/// 外部接口(OSPP、华为会议)调用结果
+pub fn record_external_call(service: &'static str, operation: &'static str, outcome: &'static str) {
+    metrics::counter!(
+        EXTERNAL_API_CALLS_TOTAL,
+        "service" => service,
+        "operation" => operation,
+        "outcome" => outcome
+    )
+    .increment(1);
+}
+
+/// 月度积分计算耗时
+pub fn record_monthly_calculation(duration: Duration, success: bool) {
+    let outcome = if success { "success" } else { "failure" };
+    metrics::histogram!(MONTHLY_CALCULATION_DURATION_SECONDS, "outcome" => outcome)
+        .record(duration.as_secs_f64());
+}
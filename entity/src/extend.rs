@@ -1,4 +1,4 @@
-use crate::sea_orm_active_enums::TaskStatus;
+use crate::{sea_orm_active_enums::TaskStatus, task};
 
 impl TaskStatus {
     pub fn processing_task_status() -> Vec<TaskStatus> {
@@ -14,3 +14,11 @@ impl TaskStatus {
         vec![TaskStatus::Finished]
     }
 }
+
+impl task::Model {
+    /// 已完成且计入积分的任务：合同期内完成，或经导师确认
+    pub fn payable(&self) -> bool {
+        self.task_status == TaskStatus::Finished
+            && (!self.out_of_contract || self.contract_override)
+    }
+}
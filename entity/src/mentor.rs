@@ -15,6 +15,7 @@ pub struct Model {
     pub status: String,
     pub created_at: DateTime,
     pub updated_at: DateTime,
+    pub program_id: i32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -2,10 +2,18 @@
 
 pub mod prelude;
 
+pub mod closed_month;
 pub mod conference;
 pub mod extend;
 pub mod mentor;
 pub mod monthly_score;
+pub mod payout_batch;
+pub mod payout_item;
+pub mod program;
+pub mod redemption_request;
+pub mod score_adjustment;
 pub mod sea_orm_active_enums;
 pub mod student;
+pub mod student_change_log;
+pub mod student_contract;
 pub mod task;
@@ -18,6 +18,10 @@ pub struct Model {
     pub exchanged: i32,
     pub create_at: DateTime,
     pub update_at: DateTime,
+    pub score_strategy: Option<String>,
+    pub contract_end_date: Option<Date>,
+    pub tier: Option<i32>,
+    pub program_id: i32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -1,7 +1,15 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.19
 
+pub use super::closed_month::Entity as ClosedMonth;
 pub use super::conference::Entity as Conference;
 pub use super::mentor::Entity as Mentor;
 pub use super::monthly_score::Entity as MonthlyScore;
+pub use super::payout_batch::Entity as PayoutBatch;
+pub use super::payout_item::Entity as PayoutItem;
+pub use super::program::Entity as Program;
+pub use super::redemption_request::Entity as RedemptionRequest;
+pub use super::score_adjustment::Entity as ScoreAdjustment;
 pub use super::student::Entity as Student;
+pub use super::student_change_log::Entity as StudentChangeLog;
+pub use super::student_contract::Entity as StudentContract;
 pub use super::task::Entity as Task;
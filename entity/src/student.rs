@@ -12,9 +12,15 @@ pub struct Model {
     pub github_login: String,
     pub student_name: String,
     pub contract_end_date: Option<Date>,
+    pub contract_start_date: Option<Date>,
     pub create_at: DateTime,
     pub update_at: DateTime,
     pub email: String,
+    pub ospp_missing: bool,
+    pub synced_at: Option<DateTime>,
+    pub status: String,
+    pub status_reason: Option<String>,
+    pub program_id: i32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
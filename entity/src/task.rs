@@ -25,6 +25,13 @@ pub struct Model {
     pub update_at: DateTime,
     pub github_issue_title: String,
     pub github_issue_link: String,
+    pub finished_at: Option<DateTime>,
+    pub out_of_contract: bool,
+    pub contract_override: bool,
+    pub contract_override_by: Option<String>,
+    pub assigned_at: Option<DateTime>,
+    pub release_count: i32,
+    pub program_id: i32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -5,6 +5,16 @@ mod m20250114_074736_monthly_score;
 mod m20250212_084656_create_student;
 mod m20250222_082628_alter_task;
 mod m20251226_023303_create_mentor;
+mod m20260112_021845_create_payout;
+mod m20260126_074210_student_contract;
+mod m20260209_033517_redemption_request;
+mod m20260223_061204_close_month;
+mod m20260309_024516_task_stats;
+mod m20260316_081427_score_strategy;
+mod m20260330_021532_student_sync;
+mod m20260406_031204_student_deactivate;
+mod m20260413_064722_student_lifecycle;
+mod m20260420_030518_program;
 
 pub struct Migrator;
 
@@ -17,6 +27,16 @@ impl MigratorTrait for Migrator {
             Box::new(m20250212_084656_create_student::Migration),
             Box::new(m20250222_082628_alter_task::Migration),
             Box::new(m20251226_023303_create_mentor::Migration),
+            Box::new(m20260112_021845_create_payout::Migration),
+            Box::new(m20260126_074210_student_contract::Migration),
+            Box::new(m20260209_033517_redemption_request::Migration),
+            Box::new(m20260223_061204_close_month::Migration),
+            Box::new(m20260309_024516_task_stats::Migration),
+            Box::new(m20260316_081427_score_strategy::Migration),
+            Box::new(m20260330_021532_student_sync::Migration),
+            Box::new(m20260406_031204_student_deactivate::Migration),
+            Box::new(m20260413_064722_student_lifecycle::Migration),
+            Box::new(m20260420_030518_program::Migration),
         ]
     }
 }
@@ -0,0 +1,110 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PayoutBatch::Table)
+                    .if_not_exists()
+                    .col(pk_auto(PayoutBatch::Id))
+                    .col(integer(PayoutBatch::Year))
+                    .col(integer(PayoutBatch::Month))
+                    .col(string(PayoutBatch::Status))
+                    .col(string_null(PayoutBatch::ApprovedBy))
+                    .col(date_time_null(PayoutBatch::ApprovedAt))
+                    .col(date_time(PayoutBatch::CreateAt))
+                    .col(date_time(PayoutBatch::UpdateAt))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-payout_batch_year_month")
+                    .unique()
+                    .table(PayoutBatch::Table)
+                    .col(PayoutBatch::Year)
+                    .col(PayoutBatch::Month)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(PayoutItem::Table)
+                    .if_not_exists()
+                    .col(pk_auto(PayoutItem::Id))
+                    .col(integer(PayoutItem::BatchId))
+                    .col(string(PayoutItem::GithubLogin))
+                    .col(string(PayoutItem::StudentName))
+                    .col(integer(PayoutItem::ConsumptionScore))
+                    .col(integer(PayoutItem::Exchanged))
+                    .col(string(PayoutItem::Status))
+                    .col(string_null(PayoutItem::FailureReason))
+                    .col(date_time_null(PayoutItem::PaidAt))
+                    .col(date_time(PayoutItem::CreateAt))
+                    .col(date_time(PayoutItem::UpdateAt))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-payout_item_batch_login")
+                    .unique()
+                    .table(PayoutItem::Table)
+                    .col(PayoutItem::BatchId)
+                    .col(PayoutItem::GithubLogin)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PayoutItem::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(PayoutBatch::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum PayoutBatch {
+    Table,
+    Id,
+    Year,
+    Month,
+    Status,
+    ApprovedBy,
+    ApprovedAt,
+    CreateAt,
+    UpdateAt,
+}
+
+#[derive(DeriveIden)]
+enum PayoutItem {
+    Table,
+    Id,
+    BatchId,
+    GithubLogin,
+    StudentName,
+    ConsumptionScore,
+    Exchanged,
+    Status,
+    FailureReason,
+    PaidAt,
+    CreateAt,
+    UpdateAt,
+}
@@ -0,0 +1,137 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(StudentContract::Table)
+                    .if_not_exists()
+                    .col(pk_auto(StudentContract::Id))
+                    .col(string(StudentContract::GithubLogin))
+                    .col(date_null(StudentContract::StartDate))
+                    .col(date(StudentContract::EndDate))
+                    .col(date_time(StudentContract::CreateAt))
+                    .col(date_time(StudentContract::UpdateAt))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-student_contract_login")
+                    .table(StudentContract::Table)
+                    .col(StudentContract::GithubLogin)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Student::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Student::ContractStartDate).date())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Task::FinishedAt).date_time())
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Task::OutOfContract)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Task::ContractOverride)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .add_column_if_not_exists(ColumnDef::new(Task::ContractOverrideBy).string())
+                    .to_owned(),
+            )
+            .await?;
+
+        // 旧数据只记录到月份（存为当月 1 日），先补齐为当月最后一天
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"UPDATE student
+                SET contract_end_date = (date_trunc('month', contract_end_date) + interval '1 month - 1 day')::date
+                WHERE contract_end_date IS NOT NULL"#,
+            )
+            .await?;
+        // 已有学生的合同截止日期作为第一份合同，开始日期未知
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"INSERT INTO student_contract (github_login, start_date, end_date, create_at, update_at)
+                SELECT github_login, NULL, contract_end_date, now(), now()
+                FROM student WHERE contract_end_date IS NOT NULL"#,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .drop_column(Task::FinishedAt)
+                    .drop_column(Task::OutOfContract)
+                    .drop_column(Task::ContractOverride)
+                    .drop_column(Task::ContractOverrideBy)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Student::Table)
+                    .drop_column(Student::ContractStartDate)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(StudentContract::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum StudentContract {
+    Table,
+    Id,
+    GithubLogin,
+    StartDate,
+    EndDate,
+    CreateAt,
+    UpdateAt,
+}
+
+#[derive(DeriveIden)]
+enum Student {
+    Table,
+    ContractStartDate,
+}
+
+#[derive(DeriveIden)]
+enum Task {
+    Table,
+    FinishedAt,
+    OutOfContract,
+    ContractOverride,
+    ContractOverrideBy,
+}
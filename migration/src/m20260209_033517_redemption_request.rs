@@ -0,0 +1,59 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RedemptionRequest::Table)
+                    .if_not_exists()
+                    .col(pk_auto(RedemptionRequest::Id))
+                    .col(string(RedemptionRequest::GithubLogin))
+                    .col(integer(RedemptionRequest::Year))
+                    .col(integer(RedemptionRequest::Month))
+                    .col(integer(RedemptionRequest::Amount))
+                    .col(string(RedemptionRequest::Status))
+                    .col(date_time(RedemptionRequest::CreateAt))
+                    .col(date_time(RedemptionRequest::UpdateAt))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-redemption_request_login_month")
+                    .unique()
+                    .table(RedemptionRequest::Table)
+                    .col(RedemptionRequest::GithubLogin)
+                    .col(RedemptionRequest::Year)
+                    .col(RedemptionRequest::Month)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RedemptionRequest::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RedemptionRequest {
+    Table,
+    Id,
+    GithubLogin,
+    Year,
+    Month,
+    Amount,
+    Status,
+    CreateAt,
+    UpdateAt,
+}
@@ -0,0 +1,99 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ClosedMonth::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ClosedMonth::Id))
+                    .col(integer(ClosedMonth::Year))
+                    .col(integer(ClosedMonth::Month))
+                    .col(string(ClosedMonth::ClosedBy))
+                    .col(date_time(ClosedMonth::ClosedAt))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-closed_month_year_month")
+                    .unique()
+                    .table(ClosedMonth::Table)
+                    .col(ClosedMonth::Year)
+                    .col(ClosedMonth::Month)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ScoreAdjustment::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ScoreAdjustment::Id))
+                    .col(string(ScoreAdjustment::GithubLogin))
+                    .col(integer(ScoreAdjustment::Year))
+                    .col(integer(ScoreAdjustment::Month))
+                    .col(integer(ScoreAdjustment::SourceYear))
+                    .col(integer(ScoreAdjustment::SourceMonth))
+                    .col(integer(ScoreAdjustment::Delta))
+                    .col(string(ScoreAdjustment::Reason))
+                    .col(date_time(ScoreAdjustment::CreateAt))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-score_adjustment_year_month")
+                    .table(ScoreAdjustment::Table)
+                    .col(ScoreAdjustment::Year)
+                    .col(ScoreAdjustment::Month)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ScoreAdjustment::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(ClosedMonth::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum ClosedMonth {
+    Table,
+    Id,
+    Year,
+    Month,
+    ClosedBy,
+    ClosedAt,
+}
+
+#[derive(DeriveIden)]
+enum ScoreAdjustment {
+    Table,
+    Id,
+    GithubLogin,
+    Year,
+    Month,
+    SourceYear,
+    SourceMonth,
+    Delta,
+    Reason,
+    CreateAt,
+}
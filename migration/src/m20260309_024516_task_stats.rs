@@ -0,0 +1,64 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .add_column_if_not_exists(date_time_null(Task::AssignedAt))
+                    .add_column_if_not_exists(integer(Task::ReleaseCount).default(0))
+                    .to_owned(),
+            )
+            .await?;
+
+        // 历史已完成任务没有完成时间，取最后更新时间；更新时间不在完成月份时取完成月份的第一天
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"UPDATE task
+                SET finished_at = CASE
+                    WHEN finish_year IS NOT NULL AND finish_month IS NOT NULL
+                        AND (EXTRACT(YEAR FROM update_at) <> finish_year
+                            OR EXTRACT(MONTH FROM update_at) <> finish_month)
+                    THEN make_timestamp(finish_year, finish_month, 1, 0, 0, 0)
+                    ELSE update_at
+                END
+                WHERE task_status = 'Finished' AND finished_at IS NULL"#,
+            )
+            .await?;
+        // 认领中的任务最后一次更新即为认领时间，已完成任务的认领时间无从得知，不计入平均耗时
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"UPDATE task SET assigned_at = update_at
+                WHERE task_status = 'Assigned' AND assigned_at IS NULL"#,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .drop_column(Task::AssignedAt)
+                    .drop_column(Task::ReleaseCount)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Task {
+    Table,
+    AssignedAt,
+    ReleaseCount,
+}
@@ -0,0 +1,43 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MonthlyScore::Table)
+                    .add_column_if_not_exists(string_null(MonthlyScore::ScoreStrategy))
+                    .add_column_if_not_exists(date_null(MonthlyScore::ContractEndDate))
+                    .add_column_if_not_exists(integer_null(MonthlyScore::Tier))
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MonthlyScore::Table)
+                    .drop_column(MonthlyScore::ScoreStrategy)
+                    .drop_column(MonthlyScore::ContractEndDate)
+                    .drop_column(MonthlyScore::Tier)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum MonthlyScore {
+    Table,
+    ScoreStrategy,
+    ContractEndDate,
+    Tier,
+}
@@ -0,0 +1,81 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Student::Table)
+                    .add_column_if_not_exists(boolean(Student::OsppMissing).default(false))
+                    .add_column_if_not_exists(date_time_null(Student::SyncedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(StudentChangeLog::Table)
+                    .if_not_exists()
+                    .col(pk_auto(StudentChangeLog::Id))
+                    .col(string(StudentChangeLog::GithubLogin))
+                    .col(string(StudentChangeLog::Field))
+                    .col(string_null(StudentChangeLog::OldValue))
+                    .col(string_null(StudentChangeLog::NewValue))
+                    .col(string(StudentChangeLog::Source))
+                    .col(date_time(StudentChangeLog::CreateAt))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-student_change_log_login")
+                    .table(StudentChangeLog::Table)
+                    .col(StudentChangeLog::GithubLogin)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(StudentChangeLog::Table).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Student::Table)
+                    .drop_column(Student::OsppMissing)
+                    .drop_column(Student::SyncedAt)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Student {
+    Table,
+    OsppMissing,
+    SyncedAt,
+}
+
+#[derive(DeriveIden)]
+enum StudentChangeLog {
+    Table,
+    Id,
+    GithubLogin,
+    Field,
+    OldValue,
+    NewValue,
+    Source,
+    CreateAt,
+}
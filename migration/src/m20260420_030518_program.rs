@@ -0,0 +1,212 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+/// 迁移前的全部数据归入的默认项目
+const DEFAULT_PROGRAM_ID: i32 = 1;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Program::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Program::Id))
+                    .col(string_uniq(Program::Code))
+                    .col(string(Program::Name))
+                    .col(string(Program::ValidationSource).default("ospp"))
+                    .col(string_null(Program::ScoreTiers))
+                    .col(integer(Program::MonthlyCap).default(100))
+                    .col(integer(Program::ExchangeRate).default(50))
+                    .col(date_time(Program::CreateAt))
+                    .col(date_time(Program::UpdateAt))
+                    .to_owned(),
+            )
+            .await?;
+        // 新建的表中第一条记录的 id 为 1
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(Program::Table)
+                    .columns([
+                        Program::Code,
+                        Program::Name,
+                        Program::ValidationSource,
+                        Program::CreateAt,
+                        Program::UpdateAt,
+                    ])
+                    .values_panic([
+                        "r2cn".into(),
+                        "R2CN".into(),
+                        "ospp".into(),
+                        Expr::current_timestamp().into(),
+                        Expr::current_timestamp().into(),
+                    ])
+                    .on_conflict(OnConflict::column(Program::Code).do_nothing().to_owned())
+                    .to_owned(),
+            )
+            .await?;
+
+        for table in [
+            Scoped::Student,
+            Scoped::Mentor,
+            Scoped::Task,
+            Scoped::MonthlyScore,
+            Scoped::PayoutItem,
+            Scoped::PayoutBatch,
+            Scoped::ClosedMonth,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table.clone())
+                        .add_column_if_not_exists(
+                            integer(Scoped::ProgramId).default(DEFAULT_PROGRAM_ID),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+        }
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-task_program_id")
+                    .table(Scoped::Task)
+                    .col(Scoped::ProgramId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-monthly_score_program_id")
+                    .table(Scoped::MonthlyScore)
+                    .col(Scoped::ProgramId)
+                    .to_owned(),
+            )
+            .await?;
+        // 批次和关账按项目区分，同一月份每个项目各一条
+        for (table, old_index, index) in [
+            (
+                Scoped::PayoutBatch,
+                "idx-payout_batch_year_month",
+                "idx-payout_batch_program_year_month",
+            ),
+            (
+                Scoped::ClosedMonth,
+                "idx-closed_month_year_month",
+                "idx-closed_month_program_year_month",
+            ),
+        ] {
+            manager
+                .drop_index(
+                    Index::drop()
+                        .name(old_index)
+                        .table(table.clone())
+                        .to_owned(),
+                )
+                .await?;
+            manager
+                .create_index(
+                    Index::create()
+                        .if_not_exists()
+                        .name(index)
+                        .unique()
+                        .table(table)
+                        .col(Scoped::ProgramId)
+                        .col(Scoped::Year)
+                        .col(Scoped::Month)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for (table, old_index, index) in [
+            (
+                Scoped::PayoutBatch,
+                "idx-payout_batch_year_month",
+                "idx-payout_batch_program_year_month",
+            ),
+            (
+                Scoped::ClosedMonth,
+                "idx-closed_month_year_month",
+                "idx-closed_month_program_year_month",
+            ),
+        ] {
+            manager
+                .drop_index(Index::drop().name(index).table(table.clone()).to_owned())
+                .await?;
+            manager
+                .create_index(
+                    Index::create()
+                        .if_not_exists()
+                        .name(old_index)
+                        .unique()
+                        .table(table)
+                        .col(Scoped::Year)
+                        .col(Scoped::Month)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        for table in [
+            Scoped::Student,
+            Scoped::Mentor,
+            Scoped::Task,
+            Scoped::MonthlyScore,
+            Scoped::PayoutItem,
+            Scoped::PayoutBatch,
+            Scoped::ClosedMonth,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .drop_column(Scoped::ProgramId)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        manager
+            .drop_table(Table::drop().table(Program::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Program {
+    Table,
+    Id,
+    Code,
+    Name,
+    ValidationSource,
+    ScoreTiers,
+    MonthlyCap,
+    ExchangeRate,
+    CreateAt,
+    UpdateAt,
+}
+
+/// 按项目划分数据的表
+#[derive(DeriveIden, Clone)]
+enum Scoped {
+    Student,
+    Mentor,
+    Task,
+    MonthlyScore,
+    PayoutItem,
+    PayoutBatch,
+    ClosedMonth,
+    ProgramId,
+    Year,
+    Month,
+}
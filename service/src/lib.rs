@@ -1,8 +1,10 @@
 use std::sync::Arc;
 
+use ospp::{OsppClient, http::HttpOsppClient};
 use sea_orm::DatabaseConnection;
 use storage::{
-    conference_stg::ConferenceStorage, mentor_stg::MentorStorage, score_stg::ScoreStorage,
+    conference_stg::ConferenceStorage, mentor_stg::MentorStorage, payout_stg::PayoutStorage,
+    program_stg::ProgramStorage, score_stg::ScoreStorage, stats_stg::StatsStorage,
     student_stg::StudentStorage, task_stg::TaskStorage,
 };
 
@@ -13,15 +15,25 @@ pub mod storage;
 #[derive(Clone)]
 pub struct Context {
     pub services: Arc<Service>,
+    pub ospp: Arc<dyn OsppClient>,
 }
 
 impl Context {
     pub async fn new(connection: Arc<DatabaseConnection>) -> Self {
+        Self::with_ospp(connection, Arc::new(HttpOsppClient::from_env())).await
+    }
+
+    pub async fn with_ospp(connection: Arc<DatabaseConnection>, ospp: Arc<dyn OsppClient>) -> Self {
         Context {
             services: Arc::new(Service::new(connection).await),
+            ospp,
         }
     }
 
+    pub fn ospp(&self) -> Arc<dyn OsppClient> {
+        self.ospp.clone()
+    }
+
     pub fn conf_stg(&self) -> ConferenceStorage {
         self.services.conference_stg.clone()
     }
@@ -41,6 +53,18 @@ impl Context {
     pub fn mentor_stg(&self) -> MentorStorage {
         self.services.mentor_stg.clone()
     }
+
+    pub fn payout_stg(&self) -> PayoutStorage {
+        self.services.payout_stg.clone()
+    }
+
+    pub fn stats_stg(&self) -> StatsStorage {
+        self.services.stats_stg.clone()
+    }
+
+    pub fn program_stg(&self) -> ProgramStorage {
+        self.services.program_stg.clone()
+    }
 }
 
 #[derive(Clone)]
@@ -50,6 +74,9 @@ pub struct Service {
     pub score_stg: ScoreStorage,
     pub student_stg: StudentStorage,
     pub mentor_stg: MentorStorage,
+    pub payout_stg: PayoutStorage,
+    pub stats_stg: StatsStorage,
+    pub program_stg: ProgramStorage,
 }
 
 impl Service {
@@ -59,6 +86,9 @@ impl Service {
             task_stg: TaskStorage::new(connection.clone()).await,
             score_stg: ScoreStorage::new(connection.clone()).await,
             mentor_stg: MentorStorage::new(connection.clone()).await,
+            payout_stg: PayoutStorage::new(connection.clone()).await,
+            stats_stg: StatsStorage::new(connection.clone()).await,
+            program_stg: ProgramStorage::new(connection.clone()).await,
             student_stg: StudentStorage::new(connection).await,
         }
     }
@@ -0,0 +1,135 @@
+use anyhow::anyhow;
+use chrono::{DateTime, Days, NaiveDate, NaiveDateTime};
+use common::date::program_timezone;
+use entity::student_contract;
+
+/// 不带时区的日期时间格式，秒后可带小数
+const DATE_TIME_FORMATS: [&str; 3] = [
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+];
+const DATE_FORMATS: [&str; 3] = ["%Y-%m-%d", "%Y/%m/%d", "%Y%m%d"];
+
+/// 解析 OSPP 返回的合同截止日期，支持纯日期、日期时间、ISO 8601 和 Unix 时间戳(10 位秒或 13 位毫秒)
+///
+/// 带时区的时间和时间戳换算为项目时区下的日期，不带时区的直接取日期部分
+pub fn parse_contract_deadline(value: &str) -> Result<NaiveDate, anyhow::Error> {
+    let value = value.trim();
+    // 只有 10 位或 13 位的纯数字按时间戳处理，8 位数字按 20250930 形式的日期处理
+    if matches!(value.len(), 10 | 13)
+        && let Ok(timestamp) = value.parse::<i64>()
+    {
+        let time = if value.len() == 13 {
+            DateTime::from_timestamp_millis(timestamp)
+        } else {
+            DateTime::from_timestamp(timestamp, 0)
+        };
+        return time
+            .map(|time| time.with_timezone(&program_timezone()).date_naive())
+            .ok_or_else(|| anyhow!("contract deadline timestamp out of range: {value}"));
+    }
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&program_timezone()).date_naive());
+    }
+    for format in DATE_TIME_FORMATS {
+        if let Ok(time) = NaiveDateTime::parse_from_str(value, format) {
+            return Ok(time.date());
+        }
+    }
+    for format in DATE_FORMATS {
+        if let Ok(date) = NaiveDate::parse_from_str(value, format) {
+            return Ok(date);
+        }
+    }
+    Err(anyhow!("unrecognized contract deadline: {value:?}"))
+}
+
+/// 判断日期是否落在任意一份合同期内，开始日期为空表示不限制开始时间
+///
+/// 没有任何合同记录时视为不在合同期内
+pub fn within_contract(contracts: &[student_contract::Model], date: NaiveDate) -> bool {
+    contracts
+        .iter()
+        .any(|c| c.start_date.is_none_or(|start| start <= date) && date <= c.end_date)
+}
+
+/// 续签合同的开始日期：上一份合同未到期时紧接其后，否则从续签当天开始
+pub fn renewal_start(previous_end: NaiveDate, today: NaiveDate) -> NaiveDate {
+    let next_day = previous_end.checked_add_days(Days::new(1)).unwrap();
+    next_day.max(today)
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{NaiveDate, Utc};
+    use entity::student_contract;
+
+    use super::{parse_contract_deadline, renewal_start, within_contract};
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn contract(start: Option<NaiveDate>, end: NaiveDate) -> student_contract::Model {
+        student_contract::Model {
+            id: 0,
+            github_login: "alice".to_owned(),
+            start_date: start,
+            end_date: end,
+            create_at: Utc::now().naive_utc(),
+            update_at: Utc::now().naive_utc(),
+        }
+    }
+
+    #[test]
+    pub fn test_within_contract() {
+        let contracts = vec![
+            contract(None, date(2025, 6, 15)),
+            contract(Some(date(2025, 9, 1)), date(2025, 12, 31)),
+        ];
+        assert!(within_contract(&contracts, date(2025, 1, 3)));
+        assert!(within_contract(&contracts, date(2025, 6, 15)));
+        assert!(!within_contract(&contracts, date(2025, 6, 16)));
+        assert!(!within_contract(&contracts, date(2025, 8, 31)));
+        assert!(within_contract(&contracts, date(2025, 9, 1)));
+        assert!(!within_contract(&contracts, date(2026, 1, 1)));
+        assert!(!within_contract(&[], date(2026, 1, 1)));
+    }
+
+    #[test]
+    pub fn test_renewal_start() {
+        assert_eq!(
+            renewal_start(date(2025, 6, 30), date(2025, 6, 10)),
+            date(2025, 7, 1)
+        );
+        assert_eq!(
+            renewal_start(date(2025, 6, 30), date(2025, 8, 20)),
+            date(2025, 8, 20)
+        );
+    }
+
+    #[test]
+    pub fn test_parse_contract_deadline() {
+        let expected = date(2025, 9, 30);
+        for value in [
+            "2025-09-30",
+            "2025/09/30",
+            "20250930",
+            "2025-09-30 00:00:00",
+            "2025-09-30 23:59:59.999",
+            "2025-09-30T12:00:00",
+            "2025-09-30T23:59:59+08:00",
+            // 北京时间 2025-09-30 08:00
+            "2025-09-30T00:00:00Z",
+            " 1759190400 ",
+            "1759190400000",
+        ] {
+            assert_eq!(parse_contract_deadline(value).unwrap(), expected, "{value}");
+        }
+        assert!(parse_contract_deadline("").is_err());
+        assert!(parse_contract_deadline("2025-02-30").is_err());
+        assert!(parse_contract_deadline("next month").is_err());
+        assert!(parse_contract_deadline("123456").is_err());
+    }
+}
@@ -0,0 +1,159 @@
+use sea_orm::FromQueryResult;
+use serde::{Deserialize, Serialize};
+
+/// 排行榜统计区间及过滤条件，month 为空时按整年统计
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LeaderboardFilter {
+    pub year: i32,
+    pub month: Option<i32>,
+    pub owner: Option<String>,
+    pub repo: Option<String>,
+    pub mentor_github_login: Option<String>,
+    pub program_id: Option<i32>,
+}
+
+impl LeaderboardFilter {
+    /// 是否按仓库或导师过滤，这类统计只能从任务表聚合
+    pub fn scoped_to_task(&self) -> bool {
+        self.owner.is_some() || self.repo.is_some() || self.mentor_github_login.is_some()
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize, FromQueryResult)]
+pub struct StudentPoints {
+    pub github_login: String,
+    pub points: i64,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize, FromQueryResult)]
+pub struct StudentTaskRank {
+    pub github_login: String,
+    pub points: i64,
+    pub finished_tasks: i64,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RankBy {
+    #[default]
+    Points,
+    Tasks,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub rank: usize,
+    pub github_login: String,
+    pub points: i64,
+    pub finished_tasks: i64,
+}
+
+/// 合并积分和任务统计并排序，`points` 为空时直接使用任务分数
+pub fn build_leaderboard(
+    points: Option<Vec<StudentPoints>>,
+    tasks: Vec<StudentTaskRank>,
+    rank_by: RankBy,
+    limit: Option<usize>,
+) -> Vec<LeaderboardEntry> {
+    let mut entries: Vec<LeaderboardEntry> = match points {
+        Some(points) => {
+            let mut entries: Vec<LeaderboardEntry> = points
+                .into_iter()
+                .map(|p| LeaderboardEntry {
+                    rank: 0,
+                    github_login: p.github_login,
+                    points: p.points,
+                    finished_tasks: 0,
+                })
+                .collect();
+            for task in tasks {
+                match entries
+                    .iter_mut()
+                    .find(|e| e.github_login == task.github_login)
+                {
+                    Some(entry) => entry.finished_tasks = task.finished_tasks,
+                    None => entries.push(LeaderboardEntry {
+                        rank: 0,
+                        github_login: task.github_login,
+                        points: 0,
+                        finished_tasks: task.finished_tasks,
+                    }),
+                }
+            }
+            entries
+        }
+        None => tasks
+            .into_iter()
+            .map(|t| LeaderboardEntry {
+                rank: 0,
+                github_login: t.github_login,
+                points: t.points,
+                finished_tasks: t.finished_tasks,
+            })
+            .collect(),
+    };
+
+    entries.sort_by(|a, b| {
+        let (a_key, b_key) = match rank_by {
+            RankBy::Points => ((a.points, a.finished_tasks), (b.points, b.finished_tasks)),
+            RankBy::Tasks => ((a.finished_tasks, a.points), (b.finished_tasks, b.points)),
+        };
+        b_key
+            .cmp(&a_key)
+            .then_with(|| a.github_login.cmp(&b.github_login))
+    });
+    if let Some(limit) = limit {
+        entries.truncate(limit);
+    }
+    for (idx, entry) in entries.iter_mut().enumerate() {
+        entry.rank = idx + 1;
+    }
+    entries
+}
+
+#[cfg(test)]
+mod test {
+    use super::{RankBy, StudentPoints, StudentTaskRank, build_leaderboard};
+
+    fn task_rank(login: &str, points: i64, finished_tasks: i64) -> StudentTaskRank {
+        StudentTaskRank {
+            github_login: login.to_owned(),
+            points,
+            finished_tasks,
+        }
+    }
+
+    #[test]
+    pub fn test_build_leaderboard_by_points() {
+        let points = vec![
+            StudentPoints {
+                github_login: "alice".to_owned(),
+                points: 40,
+            },
+            StudentPoints {
+                github_login: "bob".to_owned(),
+                points: 60,
+            },
+        ];
+        let tasks = vec![task_rank("bob", 60, 1), task_rank("alice", 40, 3)];
+        let board = build_leaderboard(Some(points), tasks, RankBy::Points, None);
+        assert_eq!(board[0].github_login, "bob");
+        assert_eq!(board[0].rank, 1);
+        assert_eq!(board[1].github_login, "alice");
+        assert_eq!(board[1].finished_tasks, 3);
+    }
+
+    #[test]
+    pub fn test_build_leaderboard_by_tasks_with_limit() {
+        let tasks = vec![
+            task_rank("bob", 60, 1),
+            task_rank("alice", 40, 3),
+            task_rank("carol", 40, 3),
+        ];
+        let board = build_leaderboard(None, tasks, RankBy::Tasks, Some(2));
+        assert_eq!(board.len(), 2);
+        assert_eq!(board[0].github_login, "alice");
+        assert_eq!(board[1].github_login, "carol");
+        assert_eq!(board[1].rank, 2);
+    }
+}
@@ -1 +1,10 @@
+pub mod contract;
+pub mod leaderboard;
+pub mod payout;
+pub mod program;
+pub mod reconcile;
 pub mod score;
+pub mod simulation;
+pub mod stats;
+pub mod student;
+pub mod student_sync;
@@ -0,0 +1,155 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum PayoutStatus {
+    #[default]
+    Draft,
+    Approved,
+    Paid,
+    /// 部分记录发放失败，失败记录可在重新发放后再次导入回执
+    PartiallyPaid,
+    Failed,
+}
+
+impl From<String> for PayoutStatus {
+    fn from(s: String) -> Self {
+        match s.to_lowercase().as_str() {
+            "approved" => PayoutStatus::Approved,
+            "paid" => PayoutStatus::Paid,
+            "partially_paid" => PayoutStatus::PartiallyPaid,
+            "failed" => PayoutStatus::Failed,
+            _ => PayoutStatus::Draft,
+        }
+    }
+}
+
+impl From<PayoutStatus> for String {
+    fn from(v: PayoutStatus) -> Self {
+        match v {
+            PayoutStatus::Draft => "draft".to_string(),
+            PayoutStatus::Approved => "approved".to_string(),
+            PayoutStatus::Paid => "paid".to_string(),
+            PayoutStatus::PartiallyPaid => "partially_paid".to_string(),
+            PayoutStatus::Failed => "failed".to_string(),
+        }
+    }
+}
+
+impl PayoutStatus {
+    /// 根据批次内全部发放记录的状态汇总批次状态，仍有待处理记录时返回 None
+    pub fn summarize(items: &[PayoutStatus]) -> Option<PayoutStatus> {
+        if items
+            .iter()
+            .any(|s| matches!(s, PayoutStatus::Draft | PayoutStatus::Approved))
+        {
+            return None;
+        }
+        let paid = items.iter().filter(|s| **s == PayoutStatus::Paid).count();
+        Some(if paid == items.len() {
+            PayoutStatus::Paid
+        } else if paid == 0 {
+            PayoutStatus::Failed
+        } else {
+            PayoutStatus::PartiallyPaid
+        })
+    }
+}
+
+/// 银行回执中的单行结果
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
+pub struct PayoutResultRow {
+    pub github_login: String,
+    pub paid: bool,
+    pub reason: Option<String>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PayoutImportSummary {
+    pub paid: usize,
+    pub failed: usize,
+    /// 回执中存在但批次中找不到的学生
+    pub unknown: Vec<String>,
+    /// 此前已到账、本次回执被忽略的学生
+    pub already_paid: Vec<String>,
+}
+
+/// 解析银行返回的 CSV，表头需包含 github_login、status，可选 reason
+///
+/// status 支持 paid/success/成功 与 failed/fail/失败
+pub fn parse_payout_result_csv(content: &str) -> Result<Vec<PayoutResultRow>, anyhow::Error> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+    let headers = reader.headers()?.clone();
+    let position = |name: &str| headers.iter().position(|h| h.eq_ignore_ascii_case(name));
+    let login_idx =
+        position("github_login").ok_or_else(|| anyhow::anyhow!("missing column github_login"))?;
+    let status_idx = position("status").ok_or_else(|| anyhow::anyhow!("missing column status"))?;
+    let reason_idx = position("reason");
+
+    let mut rows = vec![];
+    for (line, record) in reader.records().enumerate() {
+        let record = record?;
+        let login = record.get(login_idx).unwrap_or_default();
+        if login.is_empty() {
+            continue;
+        }
+        let status = record.get(status_idx).unwrap_or_default();
+        let paid = match status.to_lowercase().as_str() {
+            "paid" | "success" | "成功" => true,
+            "failed" | "fail" | "失败" => false,
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "unknown status {} at line {}",
+                    status,
+                    line + 2
+                ));
+            }
+        };
+        let reason = reason_idx
+            .and_then(|idx| record.get(idx))
+            .filter(|r| !r.is_empty())
+            .map(|r| r.to_owned());
+        rows.push(PayoutResultRow {
+            github_login: login.to_owned(),
+            paid,
+            reason,
+        });
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PayoutStatus, parse_payout_result_csv};
+
+    #[test]
+    pub fn test_parse_payout_result_csv() {
+        let content = "github_login,status,reason\nalice,paid,\nbob, 失败 ,账户信息错误\n\n";
+        let rows = parse_payout_result_csv(content).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].paid);
+        assert_eq!(rows[0].reason, None);
+        assert!(!rows[1].paid);
+        assert_eq!(rows[1].reason.as_deref(), Some("账户信息错误"));
+    }
+
+    #[test]
+    pub fn test_parse_payout_result_csv_rejects_unknown_status() {
+        let content = "github_login,status\nalice,pending\n";
+        assert!(parse_payout_result_csv(content).is_err());
+        assert!(parse_payout_result_csv("login,status\nalice,paid\n").is_err());
+    }
+
+    #[test]
+    pub fn test_summarize_batch_status() {
+        use PayoutStatus::*;
+        assert_eq!(PayoutStatus::summarize(&[Paid, Approved]), None);
+        assert_eq!(PayoutStatus::summarize(&[Paid, Paid]), Some(Paid));
+        assert_eq!(
+            PayoutStatus::summarize(&[Paid, Failed]),
+            Some(PartiallyPaid)
+        );
+        assert_eq!(PayoutStatus::summarize(&[Failed]), Some(Failed));
+    }
+}
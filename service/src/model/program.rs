@@ -0,0 +1,225 @@
+use entity::{program, student, task};
+use serde::{Deserialize, Serialize};
+
+use crate::model::{
+    score::{CommonScore, EXCHANGE_RATE, ScoreStrategy, TieredScore},
+    simulation::RuleSet,
+};
+
+/// 默认项目(R2CN)，迁移前的全部数据都归入该项目
+pub const DEFAULT_PROGRAM_ID: i32 = 1;
+
+/// 学生身份的校验来源
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ValidationSource {
+    /// 通过 OSPP 接口校验，并参与定时同步
+    #[default]
+    Ospp,
+    /// 由管理员手动登记学生
+    Manual,
+}
+
+impl ValidationSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ValidationSource::Ospp => "ospp",
+            ValidationSource::Manual => "manual",
+        }
+    }
+}
+
+impl From<String> for ValidationSource {
+    fn from(s: String) -> Self {
+        match s.to_lowercase().as_str() {
+            "manual" => ValidationSource::Manual,
+            _ => ValidationSource::Ospp,
+        }
+    }
+}
+
+impl From<ValidationSource> for String {
+    fn from(v: ValidationSource) -> Self {
+        v.as_str().to_owned()
+    }
+}
+
+/// 将 "40,60,80,100" 形式的阶梯配置解析为积分列表
+pub fn parse_tiers(value: &str) -> Result<Vec<i32>, String> {
+    value
+        .split(',')
+        .map(|tier| {
+            tier.trim()
+                .parse()
+                .map_err(|_| format!("invalid score tier: {tier:?}"))
+        })
+        .collect()
+}
+
+pub fn format_tiers(tiers: &[i32]) -> String {
+    tiers
+        .iter()
+        .map(|tier| tier.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// 项目的兑换规则，未配置阶梯的项目使用通用规则
+pub fn program_strategy(program: &program::Model) -> Box<dyn ScoreStrategy> {
+    match program.score_tiers.as_deref().map(parse_tiers) {
+        Some(Ok(tiers)) => Box::new(TieredScore {
+            tiers,
+            cap: program.monthly_cap,
+        }),
+        Some(Err(err)) => {
+            tracing::error!("program {} has invalid tiers: {}", program.code, err);
+            Box::new(CommonScore)
+        }
+        None => Box::new(CommonScore),
+    }
+}
+
+/// 学生只能认领所属项目的任务，错误信息会由机器人回复到 issue 中
+pub fn check_same_program(student: &student::Model, task: &task::Model) -> Result<(), String> {
+    if student.program_id != task.program_id {
+        return Err(format!(
+            "@{} belongs to program {}, but this task belongs to program {}, please request tasks of your own program",
+            student.github_login, student.program_id, task.program_id
+        ));
+    }
+    Ok(())
+}
+
+/// 项目当前的兑换规则，用于模拟时作为默认的候选规则
+pub fn program_rules(program: &program::Model) -> RuleSet {
+    let default = RuleSet::default();
+    match program.score_tiers.as_deref().map(parse_tiers) {
+        Some(Ok(tiers)) => RuleSet {
+            tiers,
+            cap: program.monthly_cap,
+            exchange_rate: program.exchange_rate,
+        },
+        _ => RuleSet {
+            exchange_rate: program.exchange_rate,
+            ..default
+        },
+    }
+}
+
+/// 新建或修改项目，阶梯为空时使用通用规则
+///
+/// 同一项目的不同期次(cohort)以不同的 code 区分，例如 r2cn-2026
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProgramSetting {
+    pub code: String,
+    pub name: String,
+    #[serde(default)]
+    pub validation_source: ValidationSource,
+    pub score_tiers: Option<Vec<i32>>,
+    pub monthly_cap: Option<i32>,
+    pub exchange_rate: Option<i32>,
+}
+
+impl ProgramSetting {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.code.is_empty() || self.code.chars().any(char::is_whitespace) {
+            return Err(format!("invalid program code: {:?}", self.code));
+        }
+        if self.name.trim().is_empty() {
+            return Err("program name must not be empty".to_owned());
+        }
+        if self
+            .score_tiers
+            .as_ref()
+            .is_some_and(|tiers| tiers.is_empty())
+        {
+            return Err("score_tiers must not be empty".to_owned());
+        }
+        self.rules().validate()
+    }
+
+    pub fn rules(&self) -> RuleSet {
+        let default = RuleSet::default();
+        RuleSet {
+            tiers: self.score_tiers.clone().unwrap_or(default.tiers),
+            cap: self.monthly_cap.unwrap_or(default.cap),
+            exchange_rate: self.exchange_rate.unwrap_or(EXCHANGE_RATE),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::Utc;
+    use entity::program;
+
+    use super::{
+        DEFAULT_PROGRAM_ID, ProgramSetting, format_tiers, parse_tiers, program_rules,
+        program_strategy,
+    };
+
+    #[test]
+    pub fn test_program_strategy() {
+        assert_eq!(parse_tiers(" 20, 50 ,100"), Ok(vec![20, 50, 100]));
+        assert!(parse_tiers("20,,100").is_err());
+        assert_eq!(format_tiers(&[20, 50, 100]), "20,50,100");
+
+        let mut program = program::Model {
+            id: DEFAULT_PROGRAM_ID,
+            code: "r2cn".to_owned(),
+            name: "R2CN".to_owned(),
+            validation_source: "ospp".to_owned(),
+            score_tiers: None,
+            monthly_cap: 100,
+            exchange_rate: 50,
+            create_at: Utc::now().naive_utc(),
+            update_at: Utc::now().naive_utc(),
+        };
+        assert_eq!(program_strategy(&program).name(), "common");
+        assert_eq!(program_strategy(&program).consumed_score(57), 40);
+        assert_eq!(program_rules(&program).tiers, vec![40, 60, 80, 100]);
+
+        program.score_tiers = Some("20,50".to_owned());
+        program.monthly_cap = 50;
+        assert_eq!(program_strategy(&program).name(), "tiered");
+        assert_eq!(program_strategy(&program).consumed_score(57), 50);
+        assert_eq!(program_strategy(&program).requested_score(57, 60), 50);
+        assert_eq!(program_rules(&program).tiers, vec![20, 50]);
+        assert_eq!(program_rules(&program).cap, 50);
+    }
+
+    #[test]
+    pub fn test_program_setting_validate() {
+        let setting = ProgramSetting {
+            code: "r2cn-2026".to_owned(),
+            name: "R2CN 2026".to_owned(),
+            ..Default::default()
+        };
+        assert!(setting.validate().is_ok());
+        assert_eq!(setting.rules().tiers, vec![40, 60, 80, 100]);
+        assert!(
+            ProgramSetting {
+                code: "r2cn 2026".to_owned(),
+                ..setting.clone()
+            }
+            .validate()
+            .is_err()
+        );
+        assert!(
+            ProgramSetting {
+                score_tiers: Some(vec![]),
+                ..setting.clone()
+            }
+            .validate()
+            .is_err()
+        );
+        assert!(
+            ProgramSetting {
+                exchange_rate: Some(-1),
+                ..setting
+            }
+            .validate()
+            .is_err()
+        );
+    }
+}
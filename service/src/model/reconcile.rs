@@ -0,0 +1,173 @@
+use std::collections::BTreeMap;
+
+use entity::{monthly_score, score_adjustment};
+use serde::{Deserialize, Serialize};
+
+/// 对账时记录的调整原因前缀，这类调整计入和来源为同一月份，不参与预期值计算
+pub const RECONCILE_REASON: &str = "reconcile";
+
+/// 学生某月积分的对账结果
+///
+/// expected = task_score + moved_in - moved_out，其中 moved_in 为其他已关账月份计入本月的分数，
+/// moved_out 为本月任务因关账计入其他月份的分数
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScoreMismatch {
+    pub github_login: String,
+    pub student_name: String,
+    pub year: i32,
+    pub month: i32,
+    /// monthly_score.new_score，没有记录时为 0
+    pub actual: i32,
+    pub expected: i32,
+    pub task_score: i32,
+    pub moved_in: i32,
+    pub moved_out: i32,
+    /// 已有的对账调整
+    pub reconciled: i32,
+}
+
+impl ScoreMismatch {
+    pub fn delta(&self) -> i32 {
+        self.expected - self.actual
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReconcileReport {
+    pub year: i32,
+    pub month: i32,
+    pub checked: usize,
+    pub mismatches: Vec<ScoreMismatch>,
+    /// 是否已按差额写入调整
+    pub repaired: bool,
+}
+
+/// 按学生比较任务分数(含跨月调整)与月度积分记录，只返回不一致的学生
+///
+/// `task_scores` 为该月完成且计入积分的任务(学生, 分数)，`adjustments` 为计入该月或来源于该月的调整
+pub fn reconcile(
+    (year, month): (i32, i32),
+    scores: Vec<monthly_score::Model>,
+    task_scores: &[(String, i32)],
+    adjustments: &[score_adjustment::Model],
+) -> (usize, Vec<ScoreMismatch>) {
+    let mut rows: BTreeMap<String, ScoreMismatch> = BTreeMap::new();
+    for score in scores {
+        let entry = rows
+            .entry(score.github_login.clone())
+            .or_insert_with(|| empty_row(&score.github_login, year, month));
+        entry.student_name = score.student_name;
+        entry.actual += score.new_score;
+    }
+    for (login, score) in task_scores {
+        rows.entry(login.clone())
+            .or_insert_with(|| empty_row(login, year, month))
+            .task_score += score;
+    }
+    for adjustment in adjustments {
+        let entry = rows
+            .entry(adjustment.github_login.clone())
+            .or_insert_with(|| empty_row(&adjustment.github_login, year, month));
+        let into_month = (adjustment.year, adjustment.month) == (year, month);
+        let from_month = (adjustment.source_year, adjustment.source_month) == (year, month);
+        match (into_month, from_month) {
+            (true, true) => entry.reconciled += adjustment.delta,
+            (true, false) => entry.moved_in += adjustment.delta,
+            (false, true) => entry.moved_out += adjustment.delta,
+            (false, false) => {}
+        }
+    }
+
+    let checked = rows.len();
+    let mismatches = rows
+        .into_values()
+        .map(|mut row| {
+            row.expected = row.task_score + row.moved_in - row.moved_out;
+            row
+        })
+        .filter(|row| row.delta() != 0)
+        .collect();
+    (checked, mismatches)
+}
+
+fn empty_row(login: &str, year: i32, month: i32) -> ScoreMismatch {
+    ScoreMismatch {
+        github_login: login.to_owned(),
+        year,
+        month,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::Utc;
+    use entity::{monthly_score, score_adjustment};
+
+    use super::reconcile;
+
+    fn score(login: &str, new_score: i32) -> monthly_score::Model {
+        monthly_score::Model {
+            id: 0,
+            github_login: login.to_owned(),
+            student_name: login.to_owned(),
+            year: 2025,
+            month: 3,
+            carryover_score: 0,
+            new_score,
+            consumption_score: 0,
+            exchanged: 0,
+            create_at: Utc::now().naive_utc(),
+            update_at: Utc::now().naive_utc(),
+            score_strategy: None,
+            contract_end_date: None,
+            tier: None,
+            program_id: 1,
+        }
+    }
+
+    fn adjustment(
+        login: &str,
+        (year, month): (i32, i32),
+        source: (i32, i32),
+        delta: i32,
+    ) -> score_adjustment::Model {
+        score_adjustment::Model {
+            id: 0,
+            github_login: login.to_owned(),
+            year,
+            month,
+            source_year: source.0,
+            source_month: source.1,
+            delta,
+            reason: String::new(),
+            create_at: Utc::now().naive_utc(),
+        }
+    }
+
+    #[test]
+    pub fn test_reconcile() {
+        let tasks = vec![
+            ("alice".to_owned(), 10),
+            ("alice".to_owned(), 20),
+            ("bob".to_owned(), 15),
+            ("carol".to_owned(), 5),
+        ];
+        let adjustments = vec![
+            // 二月关账后完成的任务计入三月
+            adjustment("bob", (2025, 3), (2025, 2), 8),
+            // 三月关账后完成的任务计入四月
+            adjustment("carol", (2025, 4), (2025, 3), 5),
+        ];
+        let scores = vec![score("alice", 25), score("bob", 23), score("dave", 4)];
+        let (checked, mismatches) = reconcile((2025, 3), scores, &tasks, &adjustments);
+        assert_eq!(checked, 4);
+
+        let diffs: Vec<_> = mismatches
+            .iter()
+            .map(|row| (row.github_login.as_str(), row.expected, row.actual))
+            .collect();
+        assert_eq!(diffs, vec![("alice", 30, 25), ("dave", 0, 4)]);
+        assert_eq!(mismatches[0].delta(), 5);
+    }
+}
@@ -1,14 +1,42 @@
-use chrono::NaiveDate;
-use entity::{monthly_score, student};
+use chrono::{Datelike, NaiveDate};
+use entity::{monthly_score, program, student};
 use serde::{Deserialize, Serialize};
 
+use crate::model::program::program_strategy;
+
+/// 1积分兑换的金额(元)
+pub const EXCHANGE_RATE: i32 = 50;
+
 pub trait ScoreStrategy {
+    /// 记录到月度积分中的规则名称
+    fn name(&self) -> &'static str;
+
     fn consumed_score(&self, score: i32) -> i32;
+
+    /// 自动兑换时命中的阶梯，未达到任何阶梯时为 None
+    fn tier(&self, score: i32) -> Option<i32> {
+        let consumed = self.consumed_score(score);
+        (consumed > 0).then_some(consumed)
+    }
+
+    /// 单月最多可兑换的积分
+    fn cap(&self) -> i32 {
+        100
+    }
+
+    /// 学生主动申请兑换时实际兑换的积分，不超过积分总额和单月上限
+    fn requested_score(&self, score: i32, requested: i32) -> i32 {
+        requested.clamp(0, score.min(self.cap()).max(0))
+    }
 }
 
 /// 通用计算逻辑，大于40分按阶段发放
 pub struct CommonScore;
 impl ScoreStrategy for CommonScore {
+    fn name(&self) -> &'static str {
+        "common"
+    }
+
     fn consumed_score(&self, score: i32) -> i32 {
         if score >= 100 {
             return 100;
@@ -26,9 +54,38 @@ impl ScoreStrategy for CommonScore {
     }
 }
 
+/// 可配置的阶梯规则，按达到的最高阶梯发放且不超过单月上限
+pub struct TieredScore {
+    pub tiers: Vec<i32>,
+    pub cap: i32,
+}
+impl ScoreStrategy for TieredScore {
+    fn name(&self) -> &'static str {
+        "tiered"
+    }
+
+    fn consumed_score(&self, score: i32) -> i32 {
+        self.tiers
+            .iter()
+            .copied()
+            .filter(|tier| *tier <= score)
+            .max()
+            .unwrap_or(0)
+            .min(self.cap)
+    }
+
+    fn cap(&self) -> i32 {
+        self.cap
+    }
+}
+
 /// 截止日期规则，达到截止日期后按每月最多100分发放，不足100按实际发放
 pub struct DeadlineScore;
 impl ScoreStrategy for DeadlineScore {
+    fn name(&self) -> &'static str {
+        "contract_deadline"
+    }
+
     fn consumed_score(&self, score: i32) -> i32 {
         if score >= 100 {
             return 100;
@@ -36,16 +93,67 @@ impl ScoreStrategy for DeadlineScore {
         // 合同截止，小于100分全部发放
         score
     }
+
+    /// 截止规则不分阶梯
+    fn tier(&self, _score: i32) -> Option<i32> {
+        None
+    }
+
+    /// 合同截止后积分无法再保留，忽略学生的兑换申请
+    fn requested_score(&self, score: i32, _requested: i32) -> i32 {
+        self.consumed_score(score)
+    }
 }
 
-pub fn load_score_strategy(student: &student::Model, date: NaiveDate) -> Box<dyn ScoreStrategy> {
-    // 此处计算的时候抹去了合同的日期，只计算到月份，日期默认为1号
-    if let Some(contract_end_date) = student.contract_end_date
-        && contract_end_date <= date
-    {
+/// 合同截止规则优先，其次使用学生所属项目的规则，找不到项目时使用通用规则
+pub fn load_score_strategy(
+    student: &student::Model,
+    date: NaiveDate,
+    program: Option<&program::Model>,
+) -> Box<dyn ScoreStrategy> {
+    if contract_ended(student, date) {
         return Box::new(DeadlineScore);
     }
-    Box::new(CommonScore)
+    match program {
+        Some(program) => program_strategy(program),
+        None => Box::new(CommonScore),
+    }
+}
+
+/// 结算月份是否已到合同截止月
+pub fn contract_ended(student: &student::Model, date: NaiveDate) -> bool {
+    // 此处计算的时候抹去了合同的日期，只计算到月份，日期默认为1号
+    student
+        .contract_end_date
+        .is_some_and(|contract_end_date| contract_end_date.with_day(1).unwrap() <= date)
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum RedemptionStatus {
+    #[default]
+    Pending,
+    Applied,
+    Cancelled,
+}
+
+impl From<String> for RedemptionStatus {
+    fn from(s: String) -> Self {
+        match s.to_lowercase().as_str() {
+            "applied" => RedemptionStatus::Applied,
+            "cancelled" => RedemptionStatus::Cancelled,
+            _ => RedemptionStatus::Pending,
+        }
+    }
+}
+
+impl From<RedemptionStatus> for String {
+    fn from(v: RedemptionStatus) -> Self {
+        match v {
+            RedemptionStatus::Pending => "pending".to_string(),
+            RedemptionStatus::Applied => "applied".to_string(),
+            RedemptionStatus::Cancelled => "cancelled".to_string(),
+        }
+    }
 }
 
 #[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
@@ -59,6 +167,13 @@ pub struct ScoreDto {
     pub new_score: i32,
     pub consumption_score: i32,
     pub exchanged: i32,
+    /// 结算时使用的兑换规则
+    pub score_strategy: Option<String>,
+    /// 结算时参考的合同截止日期
+    pub contract_end_date: Option<NaiveDate>,
+    /// 自动兑换命中的阶梯，按申请数额兑换或截止规则时为空
+    pub tier: Option<i32>,
+    pub program_id: i32,
 }
 
 impl ScoreDto {
@@ -83,6 +198,72 @@ impl From<monthly_score::Model> for ScoreDto {
             new_score: value.new_score,
             consumption_score: value.consumption_score,
             exchanged: value.exchanged,
+            score_strategy: value.score_strategy,
+            program_id: value.program_id,
+            contract_end_date: value.contract_end_date,
+            tier: value.tier,
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use chrono::{NaiveDate, Utc};
+    use entity::student;
+
+    use super::{CommonScore, DeadlineScore, ScoreStrategy, load_score_strategy};
+
+    #[test]
+    pub fn test_load_score_strategy_truncates_contract_month() {
+        let mut student = student::Model {
+            id: 1,
+            github_login: "alice".to_owned(),
+            student_name: "Alice".to_owned(),
+            contract_end_date: NaiveDate::from_ymd_opt(2025, 6, 20),
+            contract_start_date: None,
+            create_at: Utc::now().naive_utc(),
+            update_at: Utc::now().naive_utc(),
+            email: String::new(),
+            ospp_missing: false,
+            synced_at: None,
+            status: "active".to_owned(),
+            status_reason: None,
+            program_id: 1,
+        };
+        // 合同在6月内到期，结算6月时已按截止规则全部发放
+        let june = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+        assert_eq!(
+            load_score_strategy(&student, june, None).consumed_score(57),
+            57
+        );
+        let may = NaiveDate::from_ymd_opt(2025, 5, 1).unwrap();
+        assert_eq!(
+            load_score_strategy(&student, may, None).consumed_score(57),
+            40
+        );
+
+        student.contract_end_date = None;
+        assert_eq!(
+            load_score_strategy(&student, june, None).consumed_score(57),
+            40
+        );
+    }
+
+    #[test]
+    pub fn test_requested_score() {
+        assert_eq!(CommonScore.requested_score(57, 20), 20);
+        assert_eq!(CommonScore.requested_score(57, 0), 0);
+        assert_eq!(CommonScore.requested_score(57, 80), 57);
+        assert_eq!(CommonScore.requested_score(150, 120), 100);
+        assert_eq!(DeadlineScore.requested_score(57, 20), 57);
+    }
+
+    #[test]
+    pub fn test_strategy_tier() {
+        assert_eq!(CommonScore.tier(57), Some(40));
+        assert_eq!(CommonScore.tier(30), None);
+        assert_eq!(DeadlineScore.tier(57), None);
+        assert_eq!(CommonScore.name(), "common");
+        assert_eq!(DeadlineScore.name(), "contract_deadline");
+    }
+}
@@ -0,0 +1,221 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Datelike, NaiveDate};
+use entity::monthly_score;
+use serde::{Deserialize, Serialize};
+
+use crate::model::score::{EXCHANGE_RATE, ScoreStrategy, TieredScore};
+
+/// 候选的积分发放规则
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
+pub struct RuleSet {
+    pub tiers: Vec<i32>,
+    pub cap: i32,
+    pub exchange_rate: i32,
+}
+
+impl Default for RuleSet {
+    /// 与当前 `CommonScore` 一致的规则
+    fn default() -> Self {
+        Self {
+            tiers: vec![40, 60, 80, 100],
+            cap: 100,
+            exchange_rate: EXCHANGE_RATE,
+        }
+    }
+}
+
+impl RuleSet {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.cap <= 0 {
+            return Err("cap must be positive".to_owned());
+        }
+        if self.exchange_rate < 0 {
+            return Err("exchange_rate must not be negative".to_owned());
+        }
+        if self.tiers.iter().any(|tier| *tier <= 0) {
+            return Err("tiers must be positive".to_owned());
+        }
+        Ok(())
+    }
+
+    /// 合同未截止按阶梯发放，截止后按上限内全部发放
+    fn consumed_score(&self, score: i32, deadline: bool) -> i32 {
+        if deadline {
+            return score.clamp(0, self.cap);
+        }
+        TieredScore {
+            tiers: self.tiers.clone(),
+            cap: self.cap,
+        }
+        .consumed_score(score)
+    }
+
+    /// 学生提交了兑换申请时按申请数额兑换，合同截止后忽略申请
+    fn requested_score(&self, score: i32, requested: i32, deadline: bool) -> i32 {
+        if deadline {
+            return self.consumed_score(score, deadline);
+        }
+        requested.clamp(0, score.min(self.cap).max(0))
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StudentSimulation {
+    pub github_login: String,
+    pub student_name: String,
+    pub actual_consumed: i32,
+    pub actual_exchanged: i32,
+    pub simulated_consumed: i32,
+    pub simulated_exchanged: i32,
+    pub exchanged_diff: i32,
+    pub actual_balance: i32,
+    pub simulated_balance: i32,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SimulationResult {
+    pub students: Vec<StudentSimulation>,
+    pub total_actual_exchanged: i32,
+    pub total_simulated_exchanged: i32,
+    pub total_exchanged_diff: i32,
+}
+
+/// 用各项目的候选规则重放月度积分历史，不写入数据，`rules` 中没有的项目按默认规则计算
+///
+/// 每个学生区间内第一个月的结转分数取实际值，之后的结转分数取模拟后的余额，
+/// 合同截止日期取结算时记录的值，`redemptions` 为按 (学生, 年, 月) 索引的未取消兑换申请数额
+pub fn simulate(
+    history: Vec<monthly_score::Model>,
+    redemptions: &HashMap<(String, i32, i32), i32>,
+    rules: &HashMap<i32, RuleSet>,
+) -> SimulationResult {
+    let default_rule = RuleSet::default();
+    let mut by_student: BTreeMap<String, Vec<monthly_score::Model>> = BTreeMap::new();
+    for record in history {
+        by_student
+            .entry(record.github_login.clone())
+            .or_default()
+            .push(record);
+    }
+
+    let mut result = SimulationResult::default();
+    for (login, mut records) in by_student {
+        records.sort_by_key(|r| (r.year, r.month));
+        let mut line = StudentSimulation {
+            github_login: login.clone(),
+            ..Default::default()
+        };
+        let mut simulated_balance = None;
+        for record in &records {
+            let date = NaiveDate::from_ymd_opt(record.year, record.month as u32, 1).unwrap();
+            // 与 `contract_ended` 一致，只比较到月份
+            let deadline = record
+                .contract_end_date
+                .is_some_and(|end_date| end_date.with_day(1).unwrap() <= date);
+            let carryover = simulated_balance.unwrap_or(record.carryover_score);
+            let sum = carryover + record.new_score;
+            let rule = rules.get(&record.program_id).unwrap_or(&default_rule);
+            let consumed = match redemptions.get(&(login.clone(), record.year, record.month)) {
+                Some(requested) => rule.requested_score(sum, *requested, deadline),
+                None => rule.consumed_score(sum, deadline),
+            };
+
+            line.student_name = record.student_name.clone();
+            line.actual_consumed += record.consumption_score;
+            line.actual_exchanged += record.exchanged;
+            line.simulated_consumed += consumed;
+            line.simulated_exchanged += consumed * rule.exchange_rate;
+            line.actual_balance =
+                record.carryover_score + record.new_score - record.consumption_score;
+            simulated_balance = Some(sum - consumed);
+        }
+        line.simulated_balance = simulated_balance.unwrap_or_default();
+        line.exchanged_diff = line.simulated_exchanged - line.actual_exchanged;
+
+        result.total_actual_exchanged += line.actual_exchanged;
+        result.total_simulated_exchanged += line.simulated_exchanged;
+        result.students.push(line);
+    }
+    result.total_exchanged_diff = result.total_simulated_exchanged - result.total_actual_exchanged;
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use chrono::{NaiveDate, Utc};
+    use entity::monthly_score;
+
+    use super::{RuleSet, simulate};
+
+    fn record(
+        year: i32,
+        month: i32,
+        carryover_score: i32,
+        new_score: i32,
+        consumption_score: i32,
+    ) -> monthly_score::Model {
+        monthly_score::Model {
+            id: 0,
+            github_login: "alice".to_owned(),
+            student_name: "Alice".to_owned(),
+            year,
+            month,
+            carryover_score,
+            new_score,
+            consumption_score,
+            exchanged: consumption_score * 50,
+            create_at: Utc::now().naive_utc(),
+            update_at: Utc::now().naive_utc(),
+            score_strategy: None,
+            contract_end_date: None,
+            tier: None,
+            program_id: 1,
+        }
+    }
+
+    #[test]
+    pub fn test_simulate_default_rule_matches_history() {
+        let history = vec![record(2025, 1, 0, 57, 40), record(2025, 2, 17, 30, 40)];
+        let result = simulate(history, &HashMap::new(), &HashMap::new());
+        assert_eq!(result.total_exchanged_diff, 0);
+        assert_eq!(result.students[0].simulated_balance, 7);
+        assert_eq!(result.students[0].actual_balance, 7);
+    }
+
+    #[test]
+    pub fn test_simulate_carries_simulated_balance() {
+        let history = vec![record(2025, 12, 0, 57, 40), record(2026, 1, 17, 30, 40)];
+        let rule = RuleSet {
+            tiers: vec![20, 40],
+            cap: 40,
+            exchange_rate: 60,
+        };
+        let result = simulate(history, &HashMap::new(), &HashMap::from([(1, rule)]));
+        let line = &result.students[0];
+        // 12月: 57 -> 40, 余 17; 1月: 17 + 30 = 47 -> 40, 余 7
+        assert_eq!(line.simulated_consumed, 80);
+        assert_eq!(line.simulated_exchanged, 4800);
+        assert_eq!(line.exchanged_diff, 4800 - 4000);
+        assert_eq!(result.total_exchanged_diff, 800);
+    }
+
+    #[test]
+    pub fn test_simulate_redemption_and_recorded_deadline() {
+        let mut ended = record(2026, 2, 7, 20, 27);
+        ended.contract_end_date = NaiveDate::from_ymd_opt(2026, 2, 15);
+        let history = vec![record(2026, 1, 0, 57, 30), ended];
+        let redemptions = HashMap::from([
+            (("alice".to_owned(), 2026, 1), 30),
+            (("alice".to_owned(), 2026, 2), 5),
+        ]);
+        let result = simulate(history, &redemptions, &HashMap::new());
+        let line = &result.students[0];
+        // 1月按申请兑换 30, 余 27; 2月合同截止忽略申请, 27 + 20 全部兑换
+        assert_eq!(line.simulated_consumed, 77);
+        assert_eq!(line.simulated_balance, 0);
+        assert_eq!(result.total_exchanged_diff, 1000);
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Days, NaiveDate, NaiveDateTime};
+use chrono_tz::Tz;
+use common::date::local_midnight_utc;
+use sea_orm::FromQueryResult;
+use serde::{Deserialize, Serialize};
+
+/// 统计区间及过滤条件，start/end 为项目时区下的日期(含)
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StatsFilter {
+    pub start: Option<NaiveDate>,
+    pub end: Option<NaiveDate>,
+    pub owner: Option<String>,
+    pub repo: Option<String>,
+    pub program_id: Option<i32>,
+}
+
+impl StatsFilter {
+    /// 换算为 UTC 时间区间，右侧开区间
+    pub fn time_range(&self, tz: Tz) -> (Option<NaiveDateTime>, Option<NaiveDateTime>) {
+        let start = self.start.map(|date| local_midnight_utc(date, tz));
+        let end = self
+            .end
+            .and_then(|date| date.checked_add_days(Days::new(1)))
+            .map(|date| local_midnight_utc(date, tz));
+        (start, end)
+    }
+
+    /// 区间覆盖的起止年月，以 year * 100 + month 表示
+    pub fn month_range(&self) -> (Option<i32>, Option<i32>) {
+        let key = |date: NaiveDate| date.year() * 100 + date.month() as i32;
+        (self.start.map(key), self.end.map(key))
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize, FromQueryResult)]
+pub struct StatusCount {
+    pub status: String,
+    pub count: i64,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize, FromQueryResult)]
+pub struct MonthCount {
+    /// YYYY-MM
+    pub month: String,
+    pub count: i64,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MonthlyTaskCount {
+    pub month: String,
+    pub created: i64,
+    pub finished: i64,
+}
+
+pub fn merge_monthly_counts(
+    created: Vec<MonthCount>,
+    finished: Vec<MonthCount>,
+) -> Vec<MonthlyTaskCount> {
+    let mut months: BTreeMap<String, MonthlyTaskCount> = BTreeMap::new();
+    for item in created {
+        months
+            .entry(item.month.clone())
+            .or_insert_with(|| MonthlyTaskCount {
+                month: item.month,
+                ..Default::default()
+            })
+            .created += item.count;
+    }
+    for item in finished {
+        months
+            .entry(item.month.clone())
+            .or_insert_with(|| MonthlyTaskCount {
+                month: item.month,
+                ..Default::default()
+            })
+            .finished += item.count;
+    }
+    months.into_values().collect()
+}
+
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize, FromQueryResult)]
+pub struct DurationRow {
+    pub finished_tasks: i64,
+    pub avg_seconds: Option<f64>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize, FromQueryResult)]
+pub struct ReleaseRow {
+    pub releases: Option<i64>,
+    pub holding: i64,
+}
+
+/// 任务认领情况：从 Assigned 到 Finished 的平均耗时，以及释放率
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AssignmentStats {
+    pub finished_tasks: i64,
+    pub avg_hours: Option<f64>,
+    pub releases: i64,
+    /// 认领次数：被释放的次数加上仍由学生持有(Assigned/RequestFinish/Finished)的任务数
+    pub assignments: i64,
+    pub release_rate: Option<f64>,
+}
+
+impl AssignmentStats {
+    pub fn new(duration: DurationRow, release: ReleaseRow) -> Self {
+        let releases = release.releases.unwrap_or_default();
+        let assignments = releases + release.holding;
+        Self {
+            finished_tasks: duration.finished_tasks,
+            avg_hours: duration.avg_seconds.map(|seconds| seconds / 3600.0),
+            releases,
+            assignments,
+            release_rate: (assignments > 0).then(|| releases as f64 / assignments as f64),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize, FromQueryResult)]
+pub struct ActiveParticipants {
+    pub students: i64,
+    pub mentors: i64,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize, FromQueryResult)]
+pub struct RepoPoints {
+    pub owner: String,
+    pub repo: String,
+    pub tasks: i64,
+    pub points: i64,
+}
+
+/// 月度积分表中的发放合计，不受仓库过滤影响
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize, FromQueryResult)]
+pub struct IssuedPoints {
+    pub new_score: Option<i64>,
+    pub consumption_score: Option<i64>,
+    pub exchanged: Option<i64>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PointsStats {
+    pub repositories: Vec<RepoPoints>,
+    pub issued: IssuedPoints,
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{NaiveDate, TimeZone, Utc};
+    use common::date::DEFAULT_PROGRAM_TIMEZONE;
+
+    use super::{
+        AssignmentStats, DurationRow, MonthCount, ReleaseRow, StatsFilter, merge_monthly_counts,
+    };
+
+    #[test]
+    pub fn test_stats_filter_range() {
+        let filter = StatsFilter {
+            start: NaiveDate::from_ymd_opt(2025, 1, 1),
+            end: NaiveDate::from_ymd_opt(2025, 3, 31),
+            ..Default::default()
+        };
+        let (start, end) = filter.time_range(DEFAULT_PROGRAM_TIMEZONE);
+        assert_eq!(
+            start,
+            Some(
+                Utc.with_ymd_and_hms(2024, 12, 31, 16, 0, 0)
+                    .unwrap()
+                    .naive_utc()
+            )
+        );
+        assert_eq!(
+            end,
+            Some(
+                Utc.with_ymd_and_hms(2025, 3, 31, 16, 0, 0)
+                    .unwrap()
+                    .naive_utc()
+            )
+        );
+        assert_eq!(filter.month_range(), (Some(202501), Some(202503)));
+    }
+
+    #[test]
+    pub fn test_merge_monthly_counts() {
+        let count = |month: &str, count| MonthCount {
+            month: month.to_owned(),
+            count,
+        };
+        let merged = merge_monthly_counts(
+            vec![count("2025-01", 3), count("2025-02", 1)],
+            vec![count("2025-02", 2), count("2025-03", 4)],
+        );
+        let merged: Vec<_> = merged
+            .iter()
+            .map(|m| (m.month.as_str(), m.created, m.finished))
+            .collect();
+        assert_eq!(
+            merged,
+            vec![("2025-01", 3, 0), ("2025-02", 1, 2), ("2025-03", 0, 4)]
+        );
+    }
+
+    #[test]
+    pub fn test_assignment_stats() {
+        let stats = AssignmentStats::new(
+            DurationRow {
+                finished_tasks: 2,
+                avg_seconds: Some(7200.0),
+            },
+            ReleaseRow {
+                releases: Some(1),
+                holding: 3,
+            },
+        );
+        assert_eq!(stats.avg_hours, Some(2.0));
+        assert_eq!(stats.assignments, 4);
+        assert_eq!(stats.release_rate, Some(0.25));
+
+        let empty = AssignmentStats::new(DurationRow::default(), ReleaseRow::default());
+        assert_eq!(empty.release_rate, None);
+    }
+}
@@ -0,0 +1,368 @@
+use chrono::{Days, NaiveDate};
+use entity::{student, student_contract};
+use serde::{Deserialize, Serialize};
+
+use crate::model::contract::within_contract;
+
+/// 学生状态，只有 Active 的学生可以认领任务、接收群发通知和参与定时同步
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StudentStatus {
+    #[default]
+    Active,
+    /// 暂停实习，可恢复
+    Paused,
+    Graduated,
+    /// 违规暂停，可恢复或封禁
+    Suspended,
+    /// 永久封禁，不可恢复
+    Banned,
+}
+
+impl StudentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StudentStatus::Active => "active",
+            StudentStatus::Paused => "paused",
+            StudentStatus::Graduated => "graduated",
+            StudentStatus::Suspended => "suspended",
+            StudentStatus::Banned => "banned",
+        }
+    }
+
+    /// 允许的状态变更
+    pub fn can_transition_to(&self, next: StudentStatus) -> bool {
+        use StudentStatus::*;
+        match self {
+            Active => matches!(next, Paused | Graduated | Suspended | Banned),
+            Paused => matches!(next, Active | Graduated | Suspended | Banned),
+            Graduated => matches!(next, Active),
+            Suspended => matches!(next, Active | Banned),
+            Banned => false,
+        }
+    }
+}
+
+impl From<String> for StudentStatus {
+    fn from(s: String) -> Self {
+        match s.to_lowercase().as_str() {
+            "active" => StudentStatus::Active,
+            "paused" => StudentStatus::Paused,
+            "graduated" => StudentStatus::Graduated,
+            "suspended" => StudentStatus::Suspended,
+            "banned" => StudentStatus::Banned,
+            _ => StudentStatus::Paused, // 未知状态不视为在读
+        }
+    }
+}
+
+impl From<StudentStatus> for String {
+    fn from(v: StudentStatus) -> Self {
+        v.as_str().to_owned()
+    }
+}
+
+/// 检查学生能否认领任务：已登记、仍被 OSPP 认可、处于 active 状态，
+/// 合同覆盖当天且剩余天数不少于完成任务所需的 `min_days`
+///
+/// 返回的错误信息会由机器人回复到 issue 中，需要说明学生该如何处理
+pub fn check_assignable(
+    login: &str,
+    student: Option<&student::Model>,
+    contracts: &[student_contract::Model],
+    today: NaiveDate,
+    min_days: u64,
+) -> Result<(), String> {
+    let Some(student) = student else {
+        return Err(format!(
+            "@{login} has not been validated as a student yet, please complete student validation before requesting tasks"
+        ));
+    };
+    if student.ospp_missing {
+        return Err(format!(
+            "@{login} can no longer be found in OSPP, please check your OSPP registration and contact the program admin"
+        ));
+    }
+    let status = StudentStatus::from(student.status.clone());
+    if status != StudentStatus::Active {
+        return Err(format!(
+            "@{login} is {}, only active students can request tasks, please contact the program admin",
+            status.as_str()
+        ));
+    }
+    // 没有合同记录的老数据以学生表中的截止日期为准
+    let end_date = contracts
+        .iter()
+        .map(|contract| contract.end_date)
+        .max()
+        .or(student.contract_end_date);
+    let Some(end_date) = end_date else {
+        return Err(format!(
+            "@{login} has no contract on record, please sign the contract before requesting tasks"
+        ));
+    };
+    if end_date < today {
+        return Err(format!(
+            "the contract of @{login} ended on {end_date}, please renew it before requesting tasks"
+        ));
+    }
+    if !contracts.is_empty() && !within_contract(contracts, today) {
+        let next_start = contracts
+            .iter()
+            .filter_map(|contract| contract.start_date)
+            .filter(|start| *start > today)
+            .min();
+        return Err(match next_start {
+            Some(start) => format!(
+                "the contract of @{login} starts on {start}, please request tasks after it starts"
+            ),
+            None => format!(
+                "@{login} has no contract covering {today}, please renew it before requesting tasks"
+            ),
+        });
+    }
+    if today
+        .checked_add_days(Days::new(min_days))
+        .is_none_or(|required| end_date < required)
+    {
+        return Err(format!(
+            "the contract of @{login} ends on {end_date}, at least {min_days} days are required to finish a task, please renew it before requesting tasks"
+        ));
+    }
+    Ok(())
+}
+
+/// 管理员修改学生信息时记录的变更来源
+pub const ADMIN_SOURCE: &str = "admin";
+
+pub const DEFAULT_PAGE_SIZE: u64 = 20;
+pub const MAX_PAGE_SIZE: u64 = 100;
+
+/// 学生列表查询，keyword 模糊匹配 GitHub ID、姓名和邮箱，page 从 1 开始
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StudentFilter {
+    pub keyword: Option<String>,
+    pub status: Option<StudentStatus>,
+    pub program_id: Option<i32>,
+    pub page: u64,
+    pub page_size: u64,
+}
+
+impl StudentFilter {
+    pub fn new(
+        keyword: Option<String>,
+        status: Option<StudentStatus>,
+        program_id: Option<i32>,
+        page: Option<u64>,
+        page_size: Option<u64>,
+    ) -> Self {
+        Self {
+            keyword: keyword
+                .map(|keyword| keyword.trim().to_owned())
+                .filter(|keyword| !keyword.is_empty()),
+            status,
+            program_id,
+            page: page.unwrap_or(1).max(1),
+            page_size: page_size
+                .unwrap_or(DEFAULT_PAGE_SIZE)
+                .clamp(1, MAX_PAGE_SIZE),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StudentPage {
+    pub total: u64,
+    pub page: u64,
+    pub page_size: u64,
+    pub items: Vec<student::Model>,
+}
+
+/// 管理员修改学生信息，为空的字段保持不变
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateStudent {
+    pub github_login: Option<String>,
+    pub student_name: Option<String>,
+    pub email: Option<String>,
+}
+
+impl UpdateStudent {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(login) = &self.github_login
+            && (login.is_empty() || login.chars().any(char::is_whitespace))
+        {
+            return Err(format!("invalid github_login: {login:?}"));
+        }
+        if let Some(name) = &self.student_name
+            && name.trim().is_empty()
+        {
+            return Err("student_name must not be empty".to_owned());
+        }
+        if let Some(email) = &self.email
+            && !is_valid_email(email)
+        {
+            return Err(format!("invalid email: {email}"));
+        }
+        Ok(())
+    }
+}
+
+/// 简单的邮箱格式校验：local@domain，域名至少包含一个点且不含空白字符
+pub fn is_valid_email(email: &str) -> bool {
+    let Some((local, domain)) = email.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && !domain.contains('@')
+        && !email.chars().any(char::is_whitespace)
+        && domain.contains('.')
+        && domain.split('.').all(|part| !part.is_empty())
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{NaiveDate, Utc};
+    use entity::{student, student_contract};
+
+    use super::{
+        MAX_PAGE_SIZE, StudentFilter, StudentStatus, UpdateStudent, check_assignable,
+        is_valid_email,
+    };
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    pub fn test_check_assignable() {
+        let today = date(2025, 7, 10);
+        let mut student = student::Model {
+            id: 1,
+            github_login: "zhangsan".to_owned(),
+            student_name: "张三".to_owned(),
+            contract_end_date: None,
+            contract_start_date: None,
+            create_at: Utc::now().naive_utc(),
+            update_at: Utc::now().naive_utc(),
+            email: "zs@example.com".to_owned(),
+            ospp_missing: false,
+            synced_at: None,
+            status: "active".to_owned(),
+            status_reason: None,
+            program_id: 1,
+        };
+        let contract = |start: Option<NaiveDate>, end: NaiveDate| student_contract::Model {
+            id: 0,
+            github_login: "zhangsan".to_owned(),
+            start_date: start,
+            end_date: end,
+            create_at: Utc::now().naive_utc(),
+            update_at: Utc::now().naive_utc(),
+        };
+        let check = |student: Option<&student::Model>, contracts: &[student_contract::Model]| {
+            check_assignable("zhangsan", student, contracts, today, 14)
+        };
+
+        assert!(check(None, &[]).unwrap_err().contains("not been validated"));
+        assert!(
+            check(Some(&student), &[])
+                .unwrap_err()
+                .contains("no contract")
+        );
+
+        let contracts = [contract(None, date(2025, 6, 30))];
+        assert!(
+            check(Some(&student), &contracts)
+                .unwrap_err()
+                .contains("ended on 2025-06-30")
+        );
+        let contracts = [
+            contract(None, date(2025, 6, 30)),
+            contract(Some(date(2025, 8, 1)), date(2025, 12, 31)),
+        ];
+        assert!(
+            check(Some(&student), &contracts)
+                .unwrap_err()
+                .contains("starts on 2025-08-01")
+        );
+        let contracts = [contract(Some(date(2025, 7, 1)), date(2025, 12, 31))];
+        assert!(check(Some(&student), &contracts).is_ok());
+        // 合同剩余天数不足以完成任务
+        let short = [contract(Some(date(2025, 7, 1)), date(2025, 7, 20))];
+        assert!(
+            check(Some(&student), &short)
+                .unwrap_err()
+                .contains("at least 14 days")
+        );
+        let exact = [contract(Some(date(2025, 7, 1)), date(2025, 7, 24))];
+        assert!(check(Some(&student), &exact).is_ok());
+
+        // 没有合同记录时使用学生表中的截止日期
+        student.contract_end_date = Some(date(2025, 9, 30));
+        assert!(check(Some(&student), &[]).is_ok());
+
+        student.status = "paused".to_owned();
+        assert!(
+            check(Some(&student), &contracts)
+                .unwrap_err()
+                .contains("paused")
+        );
+        student.status = "active".to_owned();
+        student.ospp_missing = true;
+        assert!(
+            check(Some(&student), &contracts)
+                .unwrap_err()
+                .contains("OSPP")
+        );
+    }
+
+    #[test]
+    pub fn test_student_status_transition() {
+        use StudentStatus::*;
+        assert!(Active.can_transition_to(Paused));
+        assert!(Paused.can_transition_to(Active));
+        assert!(Suspended.can_transition_to(Banned));
+        assert!(Graduated.can_transition_to(Active));
+        assert!(!Graduated.can_transition_to(Suspended));
+        assert!(!Active.can_transition_to(Active));
+        assert!(!Banned.can_transition_to(Active));
+        assert_eq!(StudentStatus::from("Suspended".to_owned()), Suspended);
+        assert_eq!(StudentStatus::from("inactive".to_owned()), Paused);
+    }
+
+    #[test]
+    pub fn test_is_valid_email() {
+        assert!(is_valid_email("zhangsan@example.com"));
+        assert!(is_valid_email("zhang.san+r2cn@mail.example.cn"));
+        assert!(!is_valid_email("zhangsan"));
+        assert!(!is_valid_email("zhangsan@localhost"));
+        assert!(!is_valid_email("@example.com"));
+        assert!(!is_valid_email("zhang san@example.com"));
+        assert!(!is_valid_email("zhangsan@example..com"));
+        assert!(!is_valid_email("a@b@example.com"));
+    }
+
+    #[test]
+    pub fn test_update_student_validate() {
+        let update = UpdateStudent {
+            email: Some("bad".to_owned()),
+            ..Default::default()
+        };
+        assert!(update.validate().is_err());
+        let update = UpdateStudent {
+            github_login: Some("zhang san".to_owned()),
+            ..Default::default()
+        };
+        assert!(update.validate().is_err());
+        let update = UpdateStudent {
+            student_name: Some("张三".to_owned()),
+            email: Some("zs@example.com".to_owned()),
+            ..Default::default()
+        };
+        assert!(update.validate().is_ok());
+
+        let filter = StudentFilter::new(Some("  ".to_owned()), None, None, Some(0), Some(1000));
+        assert_eq!(filter.keyword, None);
+        assert_eq!((filter.page, filter.page_size), (1, MAX_PAGE_SIZE));
+    }
+}
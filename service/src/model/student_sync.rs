@@ -0,0 +1,153 @@
+use chrono::NaiveDate;
+use entity::student;
+use serde::{Deserialize, Serialize};
+
+/// 定时同步 OSPP 时记录的变更来源
+pub const OSPP_SYNC_SOURCE: &str = "ospp_sync";
+
+/// 学生信息的一项变更
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub reason: Option<String>,
+}
+
+impl FieldChange {
+    pub fn new(field: &str, old_value: Option<String>, new_value: Option<String>) -> Self {
+        Self {
+            field: field.to_owned(),
+            old_value,
+            new_value,
+            reason: None,
+        }
+    }
+
+    pub fn with_reason(mut self, reason: &str) -> Self {
+        self.reason = Some(reason.to_owned());
+        self
+    }
+}
+
+/// OSPP 返回的学生信息
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct OsppStudent {
+    pub student_name: Option<String>,
+    pub email: Option<String>,
+    pub contract_end_date: Option<NaiveDate>,
+}
+
+/// 比较学生当前信息和 OSPP 返回的信息，OSPP 未返回或为空的字段不视为变更
+pub fn student_changes(student: &student::Model, ospp: &OsppStudent) -> Vec<FieldChange> {
+    let mut changes = vec![];
+    if let Some(name) = ospp.student_name.as_deref().filter(|name| !name.is_empty())
+        && name != student.student_name
+    {
+        changes.push(FieldChange::new(
+            "student_name",
+            Some(student.student_name.clone()),
+            Some(name.to_owned()),
+        ));
+    }
+    if let Some(email) = ospp.email.as_deref().filter(|email| !email.is_empty())
+        && email != student.email
+    {
+        changes.push(FieldChange::new(
+            "email",
+            Some(student.email.clone()),
+            Some(email.to_owned()),
+        ));
+    }
+    if let Some(end_date) = ospp.contract_end_date
+        && Some(end_date) != student.contract_end_date
+    {
+        changes.push(FieldChange::new(
+            "contract_end_date",
+            student.contract_end_date.map(|date| date.to_string()),
+            Some(end_date.to_string()),
+        ));
+    }
+    if student.ospp_missing {
+        changes.push(FieldChange::new(
+            "ospp_missing",
+            Some(true.to_string()),
+            Some(false.to_string()),
+        ));
+    }
+    changes
+}
+
+/// 一轮同步的结果
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StudentSyncSummary {
+    pub checked: usize,
+    /// 信息有变化的学生数
+    pub updated: usize,
+    /// 本轮新标记为 OSPP 不存在的学生数
+    pub missing: usize,
+    pub failed: usize,
+    /// OSPP 不可用时提前结束本轮同步
+    pub aborted: bool,
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{NaiveDate, Utc};
+    use entity::student;
+
+    use super::{OsppStudent, student_changes};
+
+    #[test]
+    pub fn test_student_changes() {
+        let student = student::Model {
+            id: 1,
+            github_login: "zhangsan".to_owned(),
+            student_name: "张三".to_owned(),
+            contract_end_date: NaiveDate::from_ymd_opt(2025, 6, 30),
+            contract_start_date: None,
+            create_at: Utc::now().naive_utc(),
+            update_at: Utc::now().naive_utc(),
+            email: "zs@example.com".to_owned(),
+            ospp_missing: true,
+            synced_at: None,
+            status: "active".to_owned(),
+            status_reason: None,
+            program_id: 1,
+        };
+        let ospp = OsppStudent {
+            student_name: Some("张三".to_owned()),
+            email: Some(String::new()),
+            contract_end_date: NaiveDate::from_ymd_opt(2025, 9, 30),
+        };
+        let changes: Vec<_> = student_changes(&student, &ospp)
+            .into_iter()
+            .map(|change| (change.field, change.old_value, change.new_value))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (
+                    "contract_end_date".to_owned(),
+                    Some("2025-06-30".to_owned()),
+                    Some("2025-09-30".to_owned())
+                ),
+                (
+                    "ospp_missing".to_owned(),
+                    Some("true".to_owned()),
+                    Some("false".to_owned())
+                ),
+            ]
+        );
+        assert!(
+            student_changes(
+                &student::Model {
+                    ospp_missing: false,
+                    ..student
+                },
+                &OsppStudent::default()
+            )
+            .is_empty()
+        );
+    }
+}
@@ -0,0 +1,55 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        RwLock,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use async_trait::async_trait;
+
+use crate::ospp::{OsppClient, OsppError, ValidateStudentRes};
+
+/// 内存中的 OSPP 实现，用于离线测试
+#[derive(Default)]
+pub struct FakeOsppClient {
+    students: RwLock<HashMap<String, ValidateStudentRes>>,
+    unavailable: AtomicBool,
+}
+
+impl FakeOsppClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_student(&self, login: &str, student: ValidateStudentRes) {
+        self.students
+            .write()
+            .unwrap()
+            .insert(login.to_owned(), student);
+    }
+
+    /// 模拟 OSPP 不可用
+    pub fn set_unavailable(&self, unavailable: bool) {
+        self.unavailable.store(unavailable, Ordering::SeqCst);
+    }
+}
+
+#[async_trait]
+impl OsppClient for FakeOsppClient {
+    async fn validate_student(&self, login: &str) -> Result<ValidateStudentRes, OsppError> {
+        if self.unavailable.load(Ordering::SeqCst) {
+            return Err(OsppError::Unavailable("fake outage".to_owned()));
+        }
+        self.students
+            .read()
+            .unwrap()
+            .get(login)
+            .cloned()
+            .map(|student| ValidateStudentRes {
+                success: true,
+                ..student
+            })
+            .ok_or_else(|| OsppError::StudentNotFound(login.to_owned()))
+    }
+}
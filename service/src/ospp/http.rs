@@ -0,0 +1,157 @@
+use std::{env, time::Duration};
+
+use async_trait::async_trait;
+use common::metrics::record_external_call;
+use reqwest::StatusCode;
+
+use crate::ospp::{OsppClient, OsppError, OsppValidateStudentRes, ValidateStudentRes};
+
+const DEFAULT_TIMEOUT_SECS: u64 = 10;
+const DEFAULT_MAX_RETRIES: u32 = 2;
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// 通过 HTTP 调用 OSPP 接口，网络错误、超时和 5xx 响应会按指数退避重试
+#[derive(Clone)]
+pub struct HttpOsppClient {
+    client: reqwest::Client,
+    base_url: String,
+    max_retries: u32,
+    retry_delay: Duration,
+}
+
+impl HttpOsppClient {
+    pub fn new(base_url: &str, timeout: Duration, max_retries: u32) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .expect("failed to build OSPP http client");
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            max_retries,
+            retry_delay: RETRY_DELAY,
+        }
+    }
+
+    /// 读取 OSPP_API_ENDPOINT，以及可选的 OSPP_TIMEOUT_SECS 和 OSPP_MAX_RETRIES
+    pub fn from_env() -> Self {
+        let base_url =
+            env::var("OSPP_API_ENDPOINT").expect("OSPP_API_ENDPOINT is not set in .env file");
+        let timeout = env::var("OSPP_TIMEOUT_SECS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_TIMEOUT_SECS);
+        let max_retries = env::var("OSPP_MAX_RETRIES")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_MAX_RETRIES);
+        Self::new(&base_url, Duration::from_secs(timeout), max_retries)
+    }
+
+    pub fn with_retry_delay(mut self, retry_delay: Duration) -> Self {
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// 单次请求，返回值中的 bool 表示失败时是否值得重试
+    async fn request(&self, login: &str) -> Result<String, (bool, OsppError)> {
+        let url = format!("{}/api/r2cnStudent/{}", self.base_url, login);
+        let res = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|err| (true, OsppError::Unavailable(err.to_string())))?;
+        // 学生不存在时接口仍返回 200，其他状态码都视为接口不可用
+        let status = res.status();
+        if !status.is_success() {
+            let retry = status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS;
+            return Err((retry, OsppError::Unavailable(format!("status {status}"))));
+        }
+        res.text()
+            .await
+            .map_err(|err| (true, OsppError::Unavailable(err.to_string())))
+    }
+}
+
+/// 解析 OSPP 响应体，无法解析的响应视为接口不可用
+pub fn parse_response(login: &str, body: &str) -> Result<ValidateStudentRes, OsppError> {
+    let data: OsppValidateStudentRes = serde_json::from_str(body).map_err(|err| {
+        tracing::error!("JSON parse error: {}", err);
+        OsppError::Unavailable(format!("invalid response: {err}"))
+    })?;
+    if !data.student_exist {
+        return Err(OsppError::StudentNotFound(login.to_owned()));
+    }
+    Ok(ValidateStudentRes {
+        success: true,
+        student_name: data.su_student_name,
+        contract_deadline: data.contract_deadline,
+        email: data.email,
+    })
+}
+
+#[async_trait]
+impl OsppClient for HttpOsppClient {
+    async fn validate_student(&self, login: &str) -> Result<ValidateStudentRes, OsppError> {
+        let mut attempt = 0;
+        let body = loop {
+            match self.request(login).await {
+                Ok(body) => break body,
+                Err((true, err)) if attempt < self.max_retries => {
+                    tracing::warn!("ospp request failed, retrying: {}", err);
+                    tokio::time::sleep(self.retry_delay * 2u32.pow(attempt)).await;
+                    attempt += 1;
+                }
+                Err((_, err)) => {
+                    record_external_call("ospp", "validate_student", "error");
+                    return Err(err);
+                }
+            }
+        };
+        tracing::debug!("ospp api response body:{:?}", body);
+
+        let res = parse_response(login, &body);
+        let outcome = match &res {
+            Ok(_) => "success",
+            Err(OsppError::StudentNotFound(_)) => "not_found",
+            Err(OsppError::Unavailable(_)) => "invalid_response",
+        };
+        record_external_call("ospp", "validate_student", outcome);
+        res
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::ospp::{OsppClient, OsppError};
+
+    use super::{HttpOsppClient, parse_response};
+
+    #[test]
+    pub fn test_parse_response() {
+        let body = r#"{"code":0,"err_code":0,"studentExist":true,"message":"","suStudentName":"张三","contractDeadline":"2025-09-30 00:00:00","email":"zs@example.com"}"#;
+        let res = parse_response("zhangsan", body).unwrap();
+        assert_eq!(res.student_name.as_deref(), Some("张三"));
+
+        let body = r#"{"code":0,"err_code":0,"studentExist":false,"message":"not found"}"#;
+        assert_eq!(
+            parse_response("zhangsan", body),
+            Err(OsppError::StudentNotFound("zhangsan".to_owned()))
+        );
+        assert!(matches!(
+            parse_response("zhangsan", "<html>"),
+            Err(OsppError::Unavailable(_))
+        ));
+    }
+
+    #[tokio::test]
+    pub async fn test_unreachable_endpoint() {
+        let client = HttpOsppClient::new("http://127.0.0.1:1", Duration::from_millis(200), 1)
+            .with_retry_delay(Duration::from_millis(1));
+        let res = client.validate_student("zhangsan").await;
+        assert!(matches!(res, Err(OsppError::Unavailable(_))));
+    }
+}
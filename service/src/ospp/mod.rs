@@ -1,8 +1,10 @@
-use std::env;
-
-use common::errors::CommonError;
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use serde_json::Error;
+use thiserror::Error;
+
+pub mod fake;
+pub mod http;
+pub mod sync;
 
 #[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
 pub struct OsppValidateStudentRes {
@@ -21,6 +23,9 @@ pub struct OsppValidateStudentRes {
 #[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ValidateStudent {
     pub login: String,
+    /// 学生所属项目，为空时归入默认项目
+    #[serde(default)]
+    pub program_id: Option<i32>,
 }
 
 #[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
@@ -31,35 +36,18 @@ pub struct ValidateStudentRes {
     pub email: Option<String>,
 }
 
-pub async fn validate_student(json: ValidateStudent) -> Result<ValidateStudentRes, CommonError> {
-    //call ospp api check status
-    let client = reqwest::Client::new();
-    let api_host = env::var("OSPP_API_ENDPOINT").unwrap();
-    let res = client
-        .get(format!("{}/api/r2cnStudent/{}", api_host, json.login))
-        .send()
-        .await
-        .unwrap();
-    let body = res.text().await.unwrap();
-    tracing::debug!("ospp api response body:{:?}", body);
+/// OSPP 接口错误：学生不存在属于正常的校验结果，接口不可用时调用方应稍后重试
+#[derive(PartialEq, Eq, Debug, Clone, Error)]
+pub enum OsppError {
+    #[error("student {0} not found in OSPP")]
+    StudentNotFound(String),
+    #[error("OSPP unavailable: {0}")]
+    Unavailable(String),
+}
 
-    // 解析 JSON
-    let data: Result<OsppValidateStudentRes, Error> = serde_json::from_str(&body);
-    match data {
-        Ok(data) => Ok(ValidateStudentRes {
-            success: data.student_exist,
-            student_name: data.su_student_name,
-            contract_deadline: data.contract_deadline,
-            email: data.email,
-        }),
-        Err(err) => {
-            tracing::error!("JSON parse error: {}", err);
-            Ok(ValidateStudentRes {
-                success: false,
-                student_name: None,
-                contract_deadline: None,
-                email: None,
-            })
-        }
-    }
+/// OSPP 学生校验接口
+#[async_trait]
+pub trait OsppClient: Send + Sync {
+    /// 学生存在时返回姓名、合同截止日期和邮箱
+    async fn validate_student(&self, login: &str) -> Result<ValidateStudentRes, OsppError>;
 }
@@ -0,0 +1,63 @@
+use crate::{
+    model::{program::ValidationSource, student_sync::StudentSyncSummary},
+    ospp::{OsppClient, OsppError},
+    storage::{program_stg::ProgramStorage, student_stg::StudentStorage},
+};
+
+/// 逐个向 OSPP 重新校验已知学生，OSPP 不可用时结束本轮同步，避免误标记学生
+///
+/// 只同步校验来源为 OSPP 的项目下的学生
+pub async fn sync_students(
+    ospp: &dyn OsppClient,
+    students: &StudentStorage,
+    programs: &ProgramStorage,
+) -> Result<StudentSyncSummary, anyhow::Error> {
+    let ospp_programs: Vec<i32> = programs
+        .list_programs()
+        .await?
+        .into_iter()
+        .filter(|program| {
+            ValidationSource::from(program.validation_source.clone()) == ValidationSource::Ospp
+        })
+        .map(|program| program.id)
+        .collect();
+    let mut summary = StudentSyncSummary::default();
+    for student in students.get_active_students(None).await? {
+        if !ospp_programs.contains(&student.program_id) {
+            continue;
+        }
+        let login = student.github_login;
+        summary.checked += 1;
+        match ospp.validate_student(&login).await {
+            Ok(data) => match students.sync_student(&login, data).await {
+                Ok(changes) if !changes.is_empty() => {
+                    tracing::info!("student {} synced with {} changes", login, changes.len());
+                    summary.updated += 1;
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    tracing::error!("sync student {} failed: {}", login, err);
+                    summary.failed += 1;
+                }
+            },
+            Err(OsppError::StudentNotFound(_)) => match students.mark_ospp_missing(&login).await {
+                Ok(Some(_)) => {
+                    tracing::warn!("student {} is no longer recognised by OSPP", login);
+                    summary.missing += 1;
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    tracing::error!("mark student {} as missing failed: {}", login, err);
+                    summary.failed += 1;
+                }
+            },
+            Err(err @ OsppError::Unavailable(_)) => {
+                tracing::error!("student sync aborted: {}", err);
+                summary.failed += 1;
+                summary.aborted = true;
+                break;
+            }
+        }
+    }
+    Ok(summary)
+}
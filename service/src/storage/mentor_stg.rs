@@ -42,6 +42,7 @@ pub struct MentorRes {
     pub status: MentorStatus,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub program_id: i32,
 }
 
 impl From<mentor::Model> for MentorRes {
@@ -54,6 +55,7 @@ impl From<mentor::Model> for MentorRes {
             status: MentorStatus::from(value.status),
             created_at: value.created_at,
             updated_at: value.updated_at,
+            program_id: value.program_id,
         }
     }
 }
@@ -72,11 +74,16 @@ impl MentorStorage {
         MentorStorage { connection }
     }
 
-    pub async fn get_active_mentors(&self) -> Result<Vec<mentor::Model>, anyhow::Error> {
-        let record = mentor::Entity::find()
-            .filter(mentor::Column::Status.eq("active"))
-            .all(self.get_connection())
-            .await?;
+    /// 状态为 active 的导师，program_id 为空时不区分项目
+    pub async fn get_active_mentors(
+        &self,
+        program_id: Option<i32>,
+    ) -> Result<Vec<mentor::Model>, anyhow::Error> {
+        let mut query = mentor::Entity::find().filter(mentor::Column::Status.eq("active"));
+        if let Some(program_id) = program_id {
+            query = query.filter(mentor::Column::ProgramId.eq(program_id));
+        }
+        let record = query.all(self.get_connection()).await?;
         Ok(record)
     }
 
@@ -1,5 +1,8 @@
 pub mod conference_stg;
 pub mod mentor_stg;
+pub mod payout_stg;
+pub mod program_stg;
 pub mod score_stg;
+pub mod stats_stg;
 pub mod student_stg;
 pub mod task_stg;
@@ -0,0 +1,286 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use entity::{payout_batch, payout_item};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::NotSet, ColumnTrait, DatabaseConnection, DbErr, EntityTrait,
+    IntoActiveModel, ModelTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
+};
+
+use crate::model::{
+    payout::{PayoutImportSummary, PayoutResultRow, PayoutStatus},
+    score::ScoreDto,
+};
+
+#[derive(Clone)]
+pub struct PayoutStorage {
+    connection: Arc<DatabaseConnection>,
+}
+
+impl PayoutStorage {
+    pub fn get_connection(&self) -> &DatabaseConnection {
+        &self.connection
+    }
+
+    pub async fn new(connection: Arc<DatabaseConnection>) -> Self {
+        PayoutStorage { connection }
+    }
+
+    pub async fn get_batch(
+        &self,
+        year: i32,
+        month: i32,
+        program_id: i32,
+    ) -> Result<Option<payout_batch::Model>, anyhow::Error> {
+        let record = payout_batch::Entity::find()
+            .filter(payout_batch::Column::ProgramId.eq(program_id))
+            .filter(payout_batch::Column::Year.eq(year))
+            .filter(payout_batch::Column::Month.eq(month))
+            .one(self.get_connection())
+            .await?;
+        Ok(record)
+    }
+
+    pub async fn list_items(
+        &self,
+        batch_id: i32,
+        program_id: Option<i32>,
+    ) -> Result<Vec<payout_item::Model>, anyhow::Error> {
+        let mut query =
+            payout_item::Entity::find().filter(payout_item::Column::BatchId.eq(batch_id));
+        if let Some(program_id) = program_id {
+            query = query.filter(payout_item::Column::ProgramId.eq(program_id));
+        }
+        let records = query
+            .order_by_asc(payout_item::Column::GithubLogin)
+            .all(self.get_connection())
+            .await?;
+        Ok(records)
+    }
+
+    /// 学生尚未到账的发放记录(草稿、已审批和发放失败)及其所属批次，按批次先后排序
+    pub async fn list_unpaid_items_by_login(
+        &self,
+        login: &str,
+    ) -> Result<Vec<(payout_batch::Model, payout_item::Model)>, anyhow::Error> {
+        let items = payout_item::Entity::find()
+            .filter(payout_item::Column::GithubLogin.eq(login))
+            .filter(payout_item::Column::Status.ne(String::from(PayoutStatus::Paid)))
+            .all(self.get_connection())
+            .await?;
+        let batches = payout_batch::Entity::find()
+            .filter(payout_batch::Column::Id.is_in(items.iter().map(|item| item.batch_id)))
+            .order_by_asc(payout_batch::Column::Year)
+            .order_by_asc(payout_batch::Column::Month)
+            .all(self.get_connection())
+            .await?;
+        let mut records = vec![];
+        for batch in batches {
+            for item in items.iter().filter(|item| item.batch_id == batch.id) {
+                records.push((batch.clone(), item.clone()));
+            }
+        }
+        Ok(records)
+    }
+
+    /// 获取项目当月草稿批次，不存在时新建；已审批的批次不允许再修改
+    pub async fn get_or_create_draft_batch(
+        &self,
+        year: i32,
+        month: i32,
+        program_id: i32,
+    ) -> Result<payout_batch::Model, anyhow::Error> {
+        if let Some(batch) = self.get_batch(year, month, program_id).await? {
+            if PayoutStatus::from(batch.status.clone()) != PayoutStatus::Draft {
+                return Err(anyhow::anyhow!(
+                    "payout batch {}-{} is already {}, recalculation is not allowed",
+                    year,
+                    month,
+                    batch.status
+                ));
+            }
+            return Ok(batch);
+        }
+        let now = Utc::now().naive_utc();
+        let batch = payout_batch::ActiveModel {
+            id: NotSet,
+            year: Set(year),
+            month: Set(month),
+            status: Set(PayoutStatus::Draft.into()),
+            approved_by: Set(None),
+            approved_at: Set(None),
+            create_at: Set(now),
+            update_at: Set(now),
+            program_id: Set(program_id),
+        };
+        Ok(batch.insert(self.get_connection()).await?)
+    }
+
+    /// 将月度积分的兑换结果写入草稿批次，未兑换的学生不生成发放记录
+    pub async fn upsert_draft_item(
+        &self,
+        batch_id: i32,
+        score: &ScoreDto,
+    ) -> Result<(), anyhow::Error> {
+        let now = Utc::now().naive_utc();
+        let existing = payout_item::Entity::find()
+            .filter(payout_item::Column::BatchId.eq(batch_id))
+            .filter(payout_item::Column::GithubLogin.eq(&score.github_login))
+            .one(self.get_connection())
+            .await?;
+        match existing {
+            Some(item) if score.exchanged == 0 => {
+                item.delete(self.get_connection()).await?;
+            }
+            Some(item) => {
+                let mut a_model = item.into_active_model();
+                a_model.student_name = Set(score.student_name.clone());
+                a_model.consumption_score = Set(score.consumption_score);
+                a_model.exchanged = Set(score.exchanged);
+                a_model.program_id = Set(score.program_id);
+                a_model.update_at = Set(now);
+                a_model.update(self.get_connection()).await?;
+            }
+            None if score.exchanged == 0 => {}
+            None => {
+                let item = payout_item::ActiveModel {
+                    id: NotSet,
+                    batch_id: Set(batch_id),
+                    github_login: Set(score.github_login.clone()),
+                    student_name: Set(score.student_name.clone()),
+                    consumption_score: Set(score.consumption_score),
+                    exchanged: Set(score.exchanged),
+                    status: Set(PayoutStatus::Draft.into()),
+                    failure_reason: Set(None),
+                    paid_at: Set(None),
+                    create_at: Set(now),
+                    update_at: Set(now),
+                    program_id: Set(score.program_id),
+                };
+                item.insert(self.get_connection()).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 审批草稿批次，批次内全部发放记录随之进入 approved
+    pub async fn approve_batch(
+        &self,
+        year: i32,
+        month: i32,
+        program_id: i32,
+        approver: &str,
+    ) -> Result<payout_batch::Model, anyhow::Error> {
+        let batch = self
+            .get_batch(year, month, program_id)
+            .await?
+            .ok_or_else(|| {
+                DbErr::RecordNotFound(format!("Payout batch not found for {}-{}", year, month))
+            })?;
+        if PayoutStatus::from(batch.status.clone()) != PayoutStatus::Draft {
+            return Err(anyhow::anyhow!(
+                "payout batch {}-{} is {}, only draft batch can be approved",
+                year,
+                month,
+                batch.status
+            ));
+        }
+        let now = Utc::now().naive_utc();
+        let txn = self.get_connection().begin().await?;
+        payout_item::Entity::update_many()
+            .col_expr(
+                payout_item::Column::Status,
+                String::from(PayoutStatus::Approved).into(),
+            )
+            .col_expr(payout_item::Column::UpdateAt, now.into())
+            .filter(payout_item::Column::BatchId.eq(batch.id))
+            .exec(&txn)
+            .await?;
+
+        let mut a_model = batch.into_active_model();
+        a_model.status = Set(PayoutStatus::Approved.into());
+        a_model.approved_by = Set(Some(approver.to_owned()));
+        a_model.approved_at = Set(Some(now));
+        a_model.update_at = Set(now);
+        let updated = a_model.update(&txn).await?;
+        txn.commit().await?;
+        Ok(updated)
+    }
+
+    /// 导入银行回执，全部记录处理完成后按结果将批次标记为 paid、partially_paid 或 failed
+    pub async fn apply_results(
+        &self,
+        year: i32,
+        month: i32,
+        program_id: i32,
+        rows: Vec<PayoutResultRow>,
+    ) -> Result<PayoutImportSummary, anyhow::Error> {
+        let batch = self
+            .get_batch(year, month, program_id)
+            .await?
+            .ok_or_else(|| {
+                DbErr::RecordNotFound(format!("Payout batch not found for {}-{}", year, month))
+            })?;
+        match PayoutStatus::from(batch.status.clone()) {
+            PayoutStatus::Draft => {
+                return Err(anyhow::anyhow!(
+                    "payout batch {}-{} has not been approved",
+                    year,
+                    month
+                ));
+            }
+            PayoutStatus::Paid => {
+                return Err(anyhow::anyhow!(
+                    "payout batch {}-{} has already been paid",
+                    year,
+                    month
+                ));
+            }
+            _ => {}
+        }
+        let now = Utc::now().naive_utc();
+        let items = self.list_items(batch.id, None).await?;
+        let mut summary = PayoutImportSummary::default();
+        let txn = self.get_connection().begin().await?;
+        for row in rows {
+            let Some(item) = items.iter().find(|i| i.github_login == row.github_login) else {
+                summary.unknown.push(row.github_login);
+                continue;
+            };
+            // 已到账的记录不再被后续回执修改
+            if PayoutStatus::from(item.status.clone()) == PayoutStatus::Paid {
+                summary.already_paid.push(row.github_login);
+                continue;
+            }
+            let mut a_model = item.clone().into_active_model();
+            if row.paid {
+                a_model.status = Set(PayoutStatus::Paid.into());
+                a_model.failure_reason = Set(None);
+                a_model.paid_at = Set(Some(now));
+                summary.paid += 1;
+            } else {
+                a_model.status = Set(PayoutStatus::Failed.into());
+                a_model.failure_reason = Set(row.reason);
+                summary.failed += 1;
+            }
+            a_model.update_at = Set(now);
+            a_model.update(&txn).await?;
+        }
+
+        let statuses: Vec<PayoutStatus> = payout_item::Entity::find()
+            .filter(payout_item::Column::BatchId.eq(batch.id))
+            .all(&txn)
+            .await?
+            .into_iter()
+            .map(|i| PayoutStatus::from(i.status))
+            .collect();
+        if let Some(status) = PayoutStatus::summarize(&statuses) {
+            let mut a_model = batch.into_active_model();
+            a_model.status = Set(status.into());
+            a_model.update_at = Set(now);
+            a_model.update(&txn).await?;
+        }
+        txn.commit().await?;
+        Ok(summary)
+    }
+}
@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use entity::program;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::NotSet, ColumnTrait, DatabaseConnection, DbErr, EntityTrait,
+    IntoActiveModel, QueryFilter, QueryOrder, Set,
+};
+
+use crate::model::program::{ProgramSetting, format_tiers};
+
+#[derive(Clone)]
+pub struct ProgramStorage {
+    connection: Arc<DatabaseConnection>,
+}
+
+impl ProgramStorage {
+    pub fn get_connection(&self) -> &DatabaseConnection {
+        &self.connection
+    }
+
+    pub async fn new(connection: Arc<DatabaseConnection>) -> Self {
+        ProgramStorage { connection }
+    }
+
+    pub async fn get_program(&self, id: i32) -> Result<Option<program::Model>, anyhow::Error> {
+        let record = program::Entity::find_by_id(id)
+            .one(self.get_connection())
+            .await?;
+        Ok(record)
+    }
+
+    pub async fn get_program_by_code(
+        &self,
+        code: &str,
+    ) -> Result<Option<program::Model>, anyhow::Error> {
+        let record = program::Entity::find()
+            .filter(program::Column::Code.eq(code))
+            .one(self.get_connection())
+            .await?;
+        Ok(record)
+    }
+
+    pub async fn list_programs(&self) -> Result<Vec<program::Model>, anyhow::Error> {
+        let records = program::Entity::find()
+            .order_by_asc(program::Column::Id)
+            .all(self.get_connection())
+            .await?;
+        Ok(records)
+    }
+
+    pub async fn new_program(
+        &self,
+        setting: ProgramSetting,
+    ) -> Result<program::Model, anyhow::Error> {
+        if self.get_program_by_code(&setting.code).await?.is_some() {
+            return Err(anyhow::anyhow!("program {} already exists", setting.code));
+        }
+        let now = Utc::now().naive_utc();
+        let rules = setting.rules();
+        let record = program::ActiveModel {
+            id: NotSet,
+            code: Set(setting.code),
+            name: Set(setting.name),
+            validation_source: Set(setting.validation_source.into()),
+            score_tiers: Set(setting.score_tiers.as_deref().map(format_tiers)),
+            monthly_cap: Set(rules.cap),
+            exchange_rate: Set(rules.exchange_rate),
+            create_at: Set(now),
+            update_at: Set(now),
+        };
+        Ok(record.insert(self.get_connection()).await?)
+    }
+
+    /// 修改项目设置，新的兑换规则从下一次月度结算开始生效
+    pub async fn update_program(
+        &self,
+        id: i32,
+        setting: ProgramSetting,
+    ) -> Result<program::Model, anyhow::Error> {
+        let program = self
+            .get_program(id)
+            .await?
+            .ok_or(DbErr::RecordNotFound(format!("Program not found: {}", id)))?;
+        if let Some(other) = self.get_program_by_code(&setting.code).await?
+            && other.id != id
+        {
+            return Err(anyhow::anyhow!("program {} already exists", setting.code));
+        }
+        let rules = setting.rules();
+        let mut a_model = program.into_active_model();
+        a_model.code = Set(setting.code);
+        a_model.name = Set(setting.name);
+        a_model.validation_source = Set(setting.validation_source.into());
+        a_model.score_tiers = Set(setting.score_tiers.as_deref().map(format_tiers));
+        a_model.monthly_cap = Set(rules.cap);
+        a_model.exchange_rate = Set(rules.exchange_rate);
+        a_model.update_at = Set(Utc::now().naive_utc());
+        Ok(a_model.update(self.get_connection()).await?)
+    }
+}
@@ -1,13 +1,31 @@
 use std::sync::Arc;
 
-use chrono::{Datelike, Utc};
-use entity::monthly_score::{self};
+use chrono::Utc;
+use common::date::current_year_month;
+use entity::{
+    closed_month,
+    monthly_score::{self},
+    payout_batch, redemption_request, score_adjustment,
+    sea_orm_active_enums::TaskStatus,
+    student, task,
+};
 use sea_orm::{
-    ActiveModelTrait, ActiveValue::NotSet, ColumnTrait, DatabaseConnection, EntityTrait,
-    QueryFilter, QueryOrder, Set,
+    ActiveModelTrait,
+    ActiveValue::NotSet,
+    ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    IntoActiveModel, QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
+    sea_query::{Expr, Order},
 };
 
-use crate::model::score::ScoreDto;
+use crate::{
+    model::{
+        leaderboard::StudentPoints,
+        program::DEFAULT_PROGRAM_ID,
+        reconcile::{RECONCILE_REASON, ReconcileReport, ScoreMismatch, reconcile},
+        score::{RedemptionStatus, ScoreDto},
+    },
+    storage::task_stg::payable_condition,
+};
 
 #[derive(Clone)]
 pub struct ScoreStorage {
@@ -29,13 +47,7 @@ impl ScoreStorage {
         month: i32,
         login: &str,
     ) -> Result<Option<monthly_score::Model>, anyhow::Error> {
-        let record = monthly_score::Entity::find()
-            .filter(monthly_score::Column::GithubLogin.eq(login))
-            .filter(monthly_score::Column::Year.eq(year))
-            .filter(monthly_score::Column::Month.eq(month))
-            .one(self.get_connection())
-            .await?;
-        Ok(record)
+        Ok(find_score(self.get_connection(), year, month, login).await?)
     }
 
     pub async fn get_latest_score_by_login(
@@ -51,14 +63,104 @@ impl ScoreStorage {
         Ok(record)
     }
 
+    /// 获取指定月份之前最近一个月的积分记录
+    pub async fn get_latest_score_before(
+        &self,
+        login: &str,
+        year: i32,
+        month: i32,
+    ) -> Result<Option<monthly_score::Model>, anyhow::Error> {
+        Ok(find_latest_score_before(self.get_connection(), login, year, month).await?)
+    }
+
+    /// 学生全部月份的积分记录，按月份先后排序
+    pub async fn list_score_by_login(
+        &self,
+        login: &str,
+    ) -> Result<Vec<monthly_score::Model>, anyhow::Error> {
+        let records = monthly_score::Entity::find()
+            .filter(monthly_score::Column::GithubLogin.eq(login))
+            .order_by_asc(monthly_score::Column::Year)
+            .order_by_asc(monthly_score::Column::Month)
+            .all(self.get_connection())
+            .await?;
+        Ok(records)
+    }
+
+    /// 查询指定月份的积分记录，program_id 为空时不区分项目
     pub async fn list_score_by_month(
         &self,
         year: i32,
         month: i32,
+        program_id: Option<i32>,
     ) -> Result<Vec<monthly_score::Model>, anyhow::Error> {
         let records = monthly_score::Entity::find()
             .filter(monthly_score::Column::Year.eq(year))
             .filter(monthly_score::Column::Month.eq(month))
+            .filter(program_condition(program_id))
+            .all(self.get_connection())
+            .await?;
+        Ok(records)
+    }
+
+    /// 查询起止月份(含)之间的全部积分记录
+    pub async fn list_score_between(
+        &self,
+        (start_year, start_month): (i32, i32),
+        (end_year, end_month): (i32, i32),
+        program_id: Option<i32>,
+    ) -> Result<Vec<monthly_score::Model>, anyhow::Error> {
+        let records = monthly_score::Entity::find()
+            .filter(program_condition(program_id))
+            .filter(
+                Condition::any()
+                    .add(monthly_score::Column::Year.gt(start_year))
+                    .add(
+                        Condition::all()
+                            .add(monthly_score::Column::Year.eq(start_year))
+                            .add(monthly_score::Column::Month.gte(start_month)),
+                    ),
+            )
+            .filter(
+                Condition::any()
+                    .add(monthly_score::Column::Year.lt(end_year))
+                    .add(
+                        Condition::all()
+                            .add(monthly_score::Column::Year.eq(end_year))
+                            .add(monthly_score::Column::Month.lte(end_month)),
+                    ),
+            )
+            .order_by_asc(monthly_score::Column::Year)
+            .order_by_asc(monthly_score::Column::Month)
+            .all(self.get_connection())
+            .await?;
+        Ok(records)
+    }
+
+    /// 按学生汇总指定年份(或月份)内新增的积分
+    pub async fn points_leaderboard(
+        &self,
+        year: i32,
+        month: Option<i32>,
+        program_id: Option<i32>,
+    ) -> Result<Vec<StudentPoints>, anyhow::Error> {
+        let mut query = monthly_score::Entity::find()
+            .select_only()
+            .column(monthly_score::Column::GithubLogin)
+            .column_as(monthly_score::Column::NewScore.sum(), "points")
+            .filter(monthly_score::Column::Year.eq(year))
+            .filter(program_condition(program_id));
+        if let Some(month) = month {
+            query = query.filter(monthly_score::Column::Month.eq(month));
+        }
+        let records = query
+            .group_by(monthly_score::Column::GithubLogin)
+            .having(Expr::expr(monthly_score::Column::NewScore.sum()).gt(0))
+            .order_by(
+                Expr::col(monthly_score::Column::NewScore).sum(),
+                Order::Desc,
+            )
+            .into_model::<StudentPoints>()
             .all(self.get_connection())
             .await?;
         Ok(records)
@@ -72,12 +174,31 @@ impl ScoreStorage {
         Ok(score)
     }
 
+    /// 更新月度积分，已关账月份的记录不允许修改
     pub async fn update_score(
         &self,
         active_model: monthly_score::ActiveModel,
     ) -> Result<monthly_score::Model, anyhow::Error> {
-        let model = active_model.update(self.get_connection()).await?;
-        Ok(model)
+        update_score(self.get_connection(), active_model).await
+    }
+
+    /// 将分数计入学生指定月份的新增积分，返回计入后的积分余额
+    ///
+    /// 目标月份已结算或关账时计入当前月份，并记录一条调整说明来源月份
+    pub async fn add_new_score(
+        &self,
+        year: i32,
+        month: i32,
+        login: &str,
+        student_name: &str,
+        score: i32,
+        reason: &str,
+    ) -> Result<i32, anyhow::Error> {
+        let txn = self.get_connection().begin().await?;
+        let balance =
+            add_new_score(&txn, (year, month), login, student_name, score, reason).await?;
+        txn.commit().await?;
+        Ok(balance)
     }
 
     pub async fn insert_or_update_carryover_score(
@@ -85,8 +206,7 @@ impl ScoreStorage {
         last_month: ScoreDto,
     ) -> Result<(), anyhow::Error> {
         let now = Utc::now().naive_utc();
-        let year = now.year();
-        let month = now.month() as i32;
+        let (year, month) = current_year_month();
 
         let balance = last_month.score_balance();
         let current_month = self
@@ -103,17 +223,565 @@ impl ScoreStorage {
                 id: NotSet,
                 github_login: Set(last_month.github_login),
                 student_name: Set(last_month.student_name),
-                year: Set(now.year()),
-                month: Set(now.month() as i32),
+                year: Set(year),
+                month: Set(month),
                 carryover_score: Set(balance),
                 new_score: Set(0),
                 consumption_score: Set(0),
                 exchanged: Set(0),
                 create_at: Set(now),
                 update_at: Set(now),
+                score_strategy: NotSet,
+                contract_end_date: NotSet,
+                tier: NotSet,
+                program_id: Set(last_month.program_id),
             };
             self.insert_score(new_score).await.unwrap();
         }
         Ok(())
     }
+
+    /// 学生当前可兑换的积分：本月记录的结转加新增，本月尚无记录时取最近一个月的余额
+    pub async fn current_balance(
+        &self,
+        year: i32,
+        month: i32,
+        login: &str,
+    ) -> Result<i32, anyhow::Error> {
+        if let Some(current) = self.get_score(year, month, login).await? {
+            return Ok(ScoreDto::from(current).score_total());
+        }
+        let balance = self
+            .get_latest_score_before(login, year, month)
+            .await?
+            .map(|score| ScoreDto::from(score).score_balance())
+            .unwrap_or_default();
+        Ok(balance)
+    }
+
+    pub async fn get_redemption_request(
+        &self,
+        year: i32,
+        month: i32,
+        login: &str,
+    ) -> Result<Option<redemption_request::Model>, anyhow::Error> {
+        let record = redemption_request::Entity::find()
+            .filter(redemption_request::Column::GithubLogin.eq(login))
+            .filter(redemption_request::Column::Year.eq(year))
+            .filter(redemption_request::Column::Month.eq(month))
+            .one(self.get_connection())
+            .await?;
+        Ok(record)
+    }
+
+    /// 指定学生未取消的兑换申请
+    pub async fn list_active_redemption_requests(
+        &self,
+        logins: Vec<String>,
+    ) -> Result<Vec<redemption_request::Model>, anyhow::Error> {
+        let cancelled: String = RedemptionStatus::Cancelled.into();
+        let records = redemption_request::Entity::find()
+            .filter(redemption_request::Column::GithubLogin.is_in(logins))
+            .filter(redemption_request::Column::Status.ne(cancelled))
+            .all(self.get_connection())
+            .await?;
+        Ok(records)
+    }
+
+    /// 提交或修改当月兑换申请，已结算的申请不能再修改
+    pub async fn save_redemption_request(
+        &self,
+        year: i32,
+        month: i32,
+        login: &str,
+        amount: i32,
+    ) -> Result<redemption_request::Model, anyhow::Error> {
+        let now = Utc::now().naive_utc();
+        let existing = self.get_redemption_request(year, month, login).await?;
+        let record = if let Some(existing) = existing {
+            if RedemptionStatus::from(existing.status.clone()) == RedemptionStatus::Applied {
+                return Err(anyhow::anyhow!(
+                    "redemption request for {}-{} has already been applied",
+                    year,
+                    month
+                ));
+            }
+            let mut a_model = existing.into_active_model();
+            a_model.amount = Set(amount);
+            a_model.status = Set(RedemptionStatus::Pending.into());
+            a_model.update_at = Set(now);
+            a_model.update(self.get_connection()).await?
+        } else {
+            let a_model = redemption_request::ActiveModel {
+                id: NotSet,
+                github_login: Set(login.to_owned()),
+                year: Set(year),
+                month: Set(month),
+                amount: Set(amount),
+                status: Set(RedemptionStatus::Pending.into()),
+                create_at: Set(now),
+                update_at: Set(now),
+            };
+            a_model.insert(self.get_connection()).await?
+        };
+        Ok(record)
+    }
+
+    pub async fn change_redemption_status(
+        &self,
+        year: i32,
+        month: i32,
+        login: &str,
+        status: RedemptionStatus,
+    ) -> Result<redemption_request::Model, anyhow::Error> {
+        let record = self
+            .get_redemption_request(year, month, login)
+            .await?
+            .ok_or_else(|| {
+                DbErr::RecordNotFound(format!(
+                    "Redemption request not found for {} in {}-{}",
+                    login, year, month
+                ))
+            })?;
+        let mut a_model = record.into_active_model();
+        a_model.status = Set(status.into());
+        a_model.update_at = Set(Utc::now().naive_utc());
+        Ok(a_model.update(self.get_connection()).await?)
+    }
+
+    pub async fn is_month_closed(
+        &self,
+        year: i32,
+        month: i32,
+        program_id: i32,
+    ) -> Result<bool, anyhow::Error> {
+        Ok(is_month_closed(self.get_connection(), year, month, program_id).await?)
+    }
+
+    /// 项目的月度计算已生成发放批次或已关账的月份，新增积分不再计入该月
+    pub async fn is_month_settled(
+        &self,
+        year: i32,
+        month: i32,
+        program_id: i32,
+    ) -> Result<bool, anyhow::Error> {
+        Ok(is_month_settled(self.get_connection(), year, month, program_id).await?)
+    }
+
+    /// program_id 为空时返回全部项目的关账月份
+    pub async fn list_closed_months(
+        &self,
+        program_id: Option<i32>,
+    ) -> Result<Vec<closed_month::Model>, anyhow::Error> {
+        let mut query = closed_month::Entity::find();
+        if let Some(program_id) = program_id {
+            query = query.filter(closed_month::Column::ProgramId.eq(program_id));
+        }
+        let records = query
+            .order_by_desc(closed_month::Column::Year)
+            .order_by_desc(closed_month::Column::Month)
+            .all(self.get_connection())
+            .await?;
+        Ok(records)
+    }
+
+    /// 关账后该项目当月的月度积分不再修改，后续变动以调整的形式计入当前月份
+    pub async fn close_month(
+        &self,
+        year: i32,
+        month: i32,
+        program_id: i32,
+        closed_by: &str,
+    ) -> Result<closed_month::Model, anyhow::Error> {
+        if self.is_month_closed(year, month, program_id).await? {
+            return Err(anyhow::anyhow!(
+                "{}-{} has already been closed",
+                year,
+                month
+            ));
+        }
+        let record = closed_month::ActiveModel {
+            id: NotSet,
+            year: Set(year),
+            month: Set(month),
+            closed_by: Set(closed_by.to_owned()),
+            closed_at: Set(Utc::now().naive_utc()),
+            program_id: Set(program_id),
+        };
+        Ok(record.insert(self.get_connection()).await?)
+    }
+
+    pub async fn record_adjustment(
+        &self,
+        login: &str,
+        (year, month): (i32, i32),
+        (source_year, source_month): (i32, i32),
+        delta: i32,
+        reason: &str,
+    ) -> Result<score_adjustment::Model, anyhow::Error> {
+        Ok(record_adjustment(
+            self.get_connection(),
+            login,
+            (year, month),
+            (source_year, source_month),
+            delta,
+            reason,
+        )
+        .await?)
+    }
+
+    /// 查询计入指定月份的积分调整
+    pub async fn list_adjustments(
+        &self,
+        year: i32,
+        month: i32,
+    ) -> Result<Vec<score_adjustment::Model>, anyhow::Error> {
+        let records = score_adjustment::Entity::find()
+            .filter(score_adjustment::Column::Year.eq(year))
+            .filter(score_adjustment::Column::Month.eq(month))
+            .order_by_asc(score_adjustment::Column::GithubLogin)
+            .all(self.get_connection())
+            .await?;
+        Ok(records)
+    }
+
+    /// 用该月完成任务的分数及跨月调整核对月度新增积分
+    pub async fn reconcile_month(
+        &self,
+        year: i32,
+        month: i32,
+    ) -> Result<ReconcileReport, anyhow::Error> {
+        let scores = self.list_score_by_month(year, month, None).await?;
+        let task_scores: Vec<(String, i32)> = task::Entity::find()
+            .select_only()
+            .column(task::Column::StudentGithubLogin)
+            .column(task::Column::Score)
+            .filter(task::Column::FinishYear.eq(year))
+            .filter(task::Column::FinishMonth.eq(month))
+            .filter(task::Column::TaskStatus.eq(TaskStatus::Finished))
+            .filter(task::Column::StudentGithubLogin.is_not_null())
+            .filter(payable_condition())
+            .into_tuple()
+            .all(self.get_connection())
+            .await?;
+        let adjustments = score_adjustment::Entity::find()
+            .filter(
+                Condition::any()
+                    .add(
+                        Condition::all()
+                            .add(score_adjustment::Column::Year.eq(year))
+                            .add(score_adjustment::Column::Month.eq(month)),
+                    )
+                    .add(
+                        Condition::all()
+                            .add(score_adjustment::Column::SourceYear.eq(year))
+                            .add(score_adjustment::Column::SourceMonth.eq(month)),
+                    ),
+            )
+            .all(self.get_connection())
+            .await?;
+
+        let (checked, mut mismatches) =
+            reconcile((year, month), scores, &task_scores, &adjustments);
+        for mismatch in mismatches.iter_mut().filter(|m| m.student_name.is_empty()) {
+            if let Some(student) = student::Entity::find()
+                .filter(student::Column::GithubLogin.eq(&mismatch.github_login))
+                .one(self.get_connection())
+                .await?
+            {
+                mismatch.student_name = student.student_name;
+            }
+        }
+        Ok(ReconcileReport {
+            year,
+            month,
+            checked,
+            mismatches,
+            repaired: false,
+        })
+    }
+
+    /// 在同一事务中按差额修正月度新增积分并记录调整
+    pub async fn repair_mismatches(
+        &self,
+        mismatches: &[ScoreMismatch],
+    ) -> Result<(), anyhow::Error> {
+        let txn = self.get_connection().begin().await?;
+        for mismatch in mismatches {
+            repair_mismatch(&txn, mismatch).await?;
+        }
+        txn.commit().await?;
+        Ok(())
+    }
+}
+
+/// 按项目过滤月度积分，program_id 为空时不过滤
+pub fn program_condition(program_id: Option<i32>) -> Condition {
+    let mut cond = Condition::all();
+    if let Some(program_id) = program_id {
+        cond = cond.add(monthly_score::Column::ProgramId.eq(program_id));
+    }
+    cond
+}
+
+async fn find_score<C: ConnectionTrait>(
+    db: &C,
+    year: i32,
+    month: i32,
+    login: &str,
+) -> Result<Option<monthly_score::Model>, DbErr> {
+    monthly_score::Entity::find()
+        .filter(monthly_score::Column::GithubLogin.eq(login))
+        .filter(monthly_score::Column::Year.eq(year))
+        .filter(monthly_score::Column::Month.eq(month))
+        .one(db)
+        .await
+}
+
+async fn find_latest_score_before<C: ConnectionTrait>(
+    db: &C,
+    login: &str,
+    year: i32,
+    month: i32,
+) -> Result<Option<monthly_score::Model>, DbErr> {
+    monthly_score::Entity::find()
+        .filter(monthly_score::Column::GithubLogin.eq(login))
+        .filter(
+            Condition::any()
+                .add(monthly_score::Column::Year.lt(year))
+                .add(
+                    Condition::all()
+                        .add(monthly_score::Column::Year.eq(year))
+                        .add(monthly_score::Column::Month.lt(month)),
+                ),
+        )
+        .order_by_desc(monthly_score::Column::Year)
+        .order_by_desc(monthly_score::Column::Month)
+        .one(db)
+        .await
+}
+
+async fn is_month_closed<C: ConnectionTrait>(
+    db: &C,
+    year: i32,
+    month: i32,
+    program_id: i32,
+) -> Result<bool, DbErr> {
+    let record = closed_month::Entity::find()
+        .filter(closed_month::Column::ProgramId.eq(program_id))
+        .filter(closed_month::Column::Year.eq(year))
+        .filter(closed_month::Column::Month.eq(month))
+        .one(db)
+        .await?;
+    Ok(record.is_some())
+}
+
+async fn is_month_settled<C: ConnectionTrait>(
+    db: &C,
+    year: i32,
+    month: i32,
+    program_id: i32,
+) -> Result<bool, DbErr> {
+    let batch = payout_batch::Entity::find()
+        .filter(payout_batch::Column::ProgramId.eq(program_id))
+        .filter(payout_batch::Column::Year.eq(year))
+        .filter(payout_batch::Column::Month.eq(month))
+        .one(db)
+        .await?;
+    Ok(batch.is_some() || is_month_closed(db, year, month, program_id).await?)
+}
+
+/// 学生指定月份的积分所属项目，与 `add_to_month` 新建记录时的取值一致
+async fn score_program<C: ConnectionTrait>(
+    db: &C,
+    (year, month): (i32, i32),
+    login: &str,
+) -> Result<i32, DbErr> {
+    if let Some(score) = find_score(db, year, month, login).await? {
+        return Ok(score.program_id);
+    }
+    match find_latest_score_before(db, login, year, month).await? {
+        Some(last_score) => Ok(last_score.program_id),
+        None => student_program(db, login).await,
+    }
+}
+
+async fn student_program<C: ConnectionTrait>(db: &C, login: &str) -> Result<i32, DbErr> {
+    let program_id = student::Entity::find()
+        .filter(student::Column::GithubLogin.eq(login))
+        .one(db)
+        .await?
+        .map(|student| student.program_id)
+        .unwrap_or(DEFAULT_PROGRAM_ID);
+    Ok(program_id)
+}
+
+async fn update_score<C: ConnectionTrait>(
+    db: &C,
+    active_model: monthly_score::ActiveModel,
+) -> Result<monthly_score::Model, anyhow::Error> {
+    let (Some(&year), Some(&month), Some(&program_id)) = (
+        active_model.year.try_as_ref(),
+        active_model.month.try_as_ref(),
+        active_model.program_id.try_as_ref(),
+    ) else {
+        return Err(anyhow::anyhow!(
+            "year, month and program_id are required to update monthly score"
+        ));
+    };
+    ensure_month_open(db, year, month, program_id).await?;
+    Ok(active_model.update(db).await?)
+}
+
+async fn ensure_month_open<C: ConnectionTrait>(
+    db: &C,
+    year: i32,
+    month: i32,
+    program_id: i32,
+) -> Result<(), anyhow::Error> {
+    if is_month_closed(db, year, month, program_id).await? {
+        return Err(anyhow::anyhow!(
+            "{}-{} has been closed, monthly score can not be changed",
+            year,
+            month
+        ));
+    }
+    Ok(())
+}
+
+async fn add_new_score<C: ConnectionTrait>(
+    db: &C,
+    (year, month): (i32, i32),
+    login: &str,
+    student_name: &str,
+    score: i32,
+    reason: &str,
+) -> Result<i32, anyhow::Error> {
+    let program_id = score_program(db, (year, month), login).await?;
+    if !is_month_settled(db, year, month, program_id).await? {
+        return add_to_month(db, (year, month), login, student_name, score).await;
+    }
+    let current = current_year_month();
+    let balance = add_to_month(db, current, login, student_name, score).await?;
+    record_adjustment(db, login, current, (year, month), score, reason).await?;
+    Ok(balance)
+}
+
+/// 当月没有记录时以上月余额作为结转分数新建
+async fn add_to_month<C: ConnectionTrait>(
+    db: &C,
+    (year, month): (i32, i32),
+    login: &str,
+    student_name: &str,
+    score: i32,
+) -> Result<i32, anyhow::Error> {
+    let now = Utc::now().naive_utc();
+    if let Some(current_score) = find_score(db, year, month, login).await? {
+        ensure_month_open(db, year, month, current_score.program_id).await?;
+        // 在数据库中累加，避免并发计分时互相覆盖
+        monthly_score::Entity::update_many()
+            .col_expr(
+                monthly_score::Column::NewScore,
+                Expr::col(monthly_score::Column::NewScore).add(score),
+            )
+            .col_expr(monthly_score::Column::UpdateAt, Expr::value(now))
+            .filter(monthly_score::Column::Id.eq(current_score.id))
+            .exec(db)
+            .await?;
+        let updated = monthly_score::Entity::find_by_id(current_score.id)
+            .one(db)
+            .await?
+            .ok_or_else(|| DbErr::RecordNotFound(format!("monthly score {}", current_score.id)))?;
+        return Ok(ScoreDto::from(updated).score_balance());
+    }
+
+    // 新记录沿用上月记录的项目，没有历史记录时取学生所属项目
+    let (carryover_score, program_id) =
+        match find_latest_score_before(db, login, year, month).await? {
+            Some(last_score) => (
+                ScoreDto::from(last_score.clone()).score_balance(),
+                last_score.program_id,
+            ),
+            None => (0, student_program(db, login).await?),
+        };
+    let new_score = monthly_score::ActiveModel {
+        id: NotSet,
+        github_login: Set(login.to_owned()),
+        student_name: Set(student_name.to_owned()),
+        year: Set(year),
+        month: Set(month),
+        carryover_score: Set(carryover_score),
+        new_score: Set(score),
+        consumption_score: Set(0),
+        exchanged: Set(0),
+        create_at: Set(now),
+        update_at: Set(now),
+        score_strategy: NotSet,
+        contract_end_date: NotSet,
+        tier: NotSet,
+        program_id: Set(program_id),
+    };
+    new_score.insert(db).await?;
+    Ok(carryover_score + score)
+}
+
+async fn record_adjustment<C: ConnectionTrait>(
+    db: &C,
+    login: &str,
+    (year, month): (i32, i32),
+    (source_year, source_month): (i32, i32),
+    delta: i32,
+    reason: &str,
+) -> Result<score_adjustment::Model, DbErr> {
+    let record = score_adjustment::ActiveModel {
+        id: NotSet,
+        github_login: Set(login.to_owned()),
+        year: Set(year),
+        month: Set(month),
+        source_year: Set(source_year),
+        source_month: Set(source_month),
+        delta: Set(delta),
+        reason: Set(reason.to_owned()),
+        create_at: Set(Utc::now().naive_utc()),
+    };
+    record.insert(db).await
+}
+
+/// 按差额修正月度新增积分并记录调整；已结算或关账月份的差额计入当前月份
+async fn repair_mismatch<C: ConnectionTrait>(
+    db: &C,
+    mismatch: &ScoreMismatch,
+) -> Result<(), anyhow::Error> {
+    let delta = mismatch.delta();
+    if delta == 0 {
+        return Ok(());
+    }
+    let month = (mismatch.year, mismatch.month);
+    let reason = format!(
+        "{}: expected {}, actual {}",
+        RECONCILE_REASON, mismatch.expected, mismatch.actual
+    );
+    let program_id = score_program(db, month, &mismatch.github_login).await?;
+    if is_month_settled(db, month.0, month.1, program_id).await? {
+        add_new_score(
+            db,
+            month,
+            &mismatch.github_login,
+            &mismatch.student_name,
+            delta,
+            &reason,
+        )
+        .await?;
+    } else {
+        add_to_month(
+            db,
+            month,
+            &mismatch.github_login,
+            &mismatch.student_name,
+            delta,
+        )
+        .await?;
+        record_adjustment(db, &mismatch.github_login, month, month, delta, &reason).await?;
+    }
+    Ok(())
 }
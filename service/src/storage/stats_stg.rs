@@ -0,0 +1,247 @@
+use std::sync::Arc;
+
+use chrono::NaiveDateTime;
+use common::date::program_timezone;
+use entity::{monthly_score, sea_orm_active_enums::TaskStatus, task};
+use sea_orm::{
+    ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+    Select,
+    sea_query::{Alias, Expr, Order, SimpleExpr},
+};
+
+use crate::{
+    model::stats::{
+        ActiveParticipants, AssignmentStats, DurationRow, IssuedPoints, MonthCount,
+        MonthlyTaskCount, PointsStats, ReleaseRow, RepoPoints, StatsFilter, StatusCount,
+        merge_monthly_counts,
+    },
+    storage::{score_stg::program_condition, task_stg::payable_condition},
+};
+
+/// 基于任务表和月度积分表的聚合统计
+#[derive(Clone)]
+pub struct StatsStorage {
+    connection: Arc<DatabaseConnection>,
+}
+
+type TimeRange = (Option<NaiveDateTime>, Option<NaiveDateTime>);
+
+fn scoped(mut query: Select<task::Entity>, filter: &StatsFilter) -> Select<task::Entity> {
+    if let Some(owner) = &filter.owner {
+        query = query.filter(task::Column::Owner.eq(owner));
+    }
+    if let Some(repo) = &filter.repo {
+        query = query.filter(task::Column::Repo.eq(repo));
+    }
+    if let Some(program_id) = filter.program_id {
+        query = query.filter(task::Column::ProgramId.eq(program_id));
+    }
+    query
+}
+
+fn in_range(column: task::Column, (start, end): TimeRange) -> Condition {
+    let mut cond = Condition::all();
+    if let Some(start) = start {
+        cond = cond.add(column.gte(start));
+    }
+    if let Some(end) = end {
+        cond = cond.add(column.lt(end));
+    }
+    cond
+}
+
+/// 按项目时区换算后的 YYYY-MM
+fn local_month(column: &str) -> SimpleExpr {
+    Expr::cust(format!(
+        "to_char(({column} AT TIME ZONE 'UTC') AT TIME ZONE '{}', 'YYYY-MM')",
+        program_timezone().name()
+    ))
+}
+
+fn holding_status() -> Vec<TaskStatus> {
+    vec![
+        TaskStatus::Assigned,
+        TaskStatus::RequestFinish,
+        TaskStatus::Finished,
+    ]
+}
+
+impl StatsStorage {
+    pub fn get_connection(&self) -> &DatabaseConnection {
+        &self.connection
+    }
+
+    pub async fn new(connection: Arc<DatabaseConnection>) -> Self {
+        StatsStorage { connection }
+    }
+
+    /// 区间内创建的任务按状态计数
+    pub async fn tasks_by_status(
+        &self,
+        filter: &StatsFilter,
+    ) -> Result<Vec<StatusCount>, anyhow::Error> {
+        let range = filter.time_range(program_timezone());
+        let status = Expr::col(task::Column::TaskStatus).cast_as(Alias::new("text"));
+        let counts = scoped(task::Entity::find(), filter)
+            .select_only()
+            .column_as(status.clone(), "status")
+            .column_as(task::Column::Id.count(), "count")
+            .filter(in_range(task::Column::CreateAt, range))
+            .group_by(status.clone())
+            .order_by(status, Order::Asc)
+            .into_model::<StatusCount>()
+            .all(self.get_connection())
+            .await?;
+        Ok(counts)
+    }
+
+    /// 每月新建和完成的任务数
+    pub async fn monthly_task_counts(
+        &self,
+        filter: &StatsFilter,
+    ) -> Result<Vec<MonthlyTaskCount>, anyhow::Error> {
+        let range = filter.time_range(program_timezone());
+        let created = scoped(task::Entity::find(), filter)
+            .select_only()
+            .column_as(local_month("create_at"), "month")
+            .column_as(task::Column::Id.count(), "count")
+            .filter(in_range(task::Column::CreateAt, range))
+            .group_by(local_month("create_at"))
+            .into_model::<MonthCount>()
+            .all(self.get_connection())
+            .await?;
+        let finished = scoped(task::Entity::find(), filter)
+            .select_only()
+            .column_as(local_month("finished_at"), "month")
+            .column_as(task::Column::Id.count(), "count")
+            .filter(task::Column::TaskStatus.eq(TaskStatus::Finished))
+            .filter(task::Column::FinishedAt.is_not_null())
+            .filter(in_range(task::Column::FinishedAt, range))
+            .group_by(local_month("finished_at"))
+            .into_model::<MonthCount>()
+            .all(self.get_connection())
+            .await?;
+        Ok(merge_monthly_counts(created, finished))
+    }
+
+    /// 区间内完成任务的平均认领耗时，以及区间内创建任务的释放率
+    pub async fn assignment_stats(
+        &self,
+        filter: &StatsFilter,
+    ) -> Result<AssignmentStats, anyhow::Error> {
+        let range = filter.time_range(program_timezone());
+        let duration = scoped(task::Entity::find(), filter)
+            .select_only()
+            .column_as(task::Column::Id.count(), "finished_tasks")
+            .column_as(
+                Expr::cust("AVG(EXTRACT(EPOCH FROM (finished_at - assigned_at)))::float8"),
+                "avg_seconds",
+            )
+            .filter(task::Column::TaskStatus.eq(TaskStatus::Finished))
+            .filter(task::Column::AssignedAt.is_not_null())
+            .filter(task::Column::FinishedAt.is_not_null())
+            .filter(in_range(task::Column::FinishedAt, range))
+            .into_model::<DurationRow>()
+            .one(self.get_connection())
+            .await?
+            .unwrap_or_default();
+        let release = scoped(task::Entity::find(), filter)
+            .select_only()
+            .column_as(task::Column::ReleaseCount.sum(), "releases")
+            .column_as(
+                Expr::cust_with_expr(
+                    "COUNT(*) FILTER (WHERE $1)",
+                    task::Column::TaskStatus.is_in(holding_status()),
+                ),
+                "holding",
+            )
+            .filter(in_range(task::Column::CreateAt, range))
+            .into_model::<ReleaseRow>()
+            .one(self.get_connection())
+            .await?
+            .unwrap_or_default();
+        Ok(AssignmentStats::new(duration, release))
+    }
+
+    /// 区间内有任务被认领或完成的学生和导师数
+    pub async fn active_participants(
+        &self,
+        filter: &StatsFilter,
+    ) -> Result<ActiveParticipants, anyhow::Error> {
+        let range = filter.time_range(program_timezone());
+        let mut query = scoped(task::Entity::find(), filter);
+        if range.0.is_some() || range.1.is_some() {
+            query = query.filter(
+                Condition::any()
+                    .add(in_range(task::Column::AssignedAt, range))
+                    .add(in_range(task::Column::FinishedAt, range)),
+            );
+        }
+        let participants = query
+            .select_only()
+            .column_as(
+                Expr::col(task::Column::StudentGithubLogin).count_distinct(),
+                "students",
+            )
+            .column_as(
+                Expr::col(task::Column::MentorGithubLogin).count_distinct(),
+                "mentors",
+            )
+            .filter(task::Column::StudentGithubLogin.is_not_null())
+            .filter(task::Column::TaskStatus.is_in(holding_status()))
+            .into_model::<ActiveParticipants>()
+            .one(self.get_connection())
+            .await?
+            .unwrap_or_default();
+        Ok(participants)
+    }
+
+    /// 各仓库完成任务发放的积分，以及月度积分表中的发放合计
+    pub async fn points_stats(&self, filter: &StatsFilter) -> Result<PointsStats, anyhow::Error> {
+        let range = filter.time_range(program_timezone());
+        let repositories = scoped(task::Entity::find(), filter)
+            .select_only()
+            .column(task::Column::Owner)
+            .column(task::Column::Repo)
+            .column_as(task::Column::Id.count(), "tasks")
+            .column_as(task::Column::Score.sum(), "points")
+            .filter(task::Column::TaskStatus.eq(TaskStatus::Finished))
+            .filter(payable_condition())
+            .filter(in_range(task::Column::FinishedAt, range))
+            .group_by(task::Column::Owner)
+            .group_by(task::Column::Repo)
+            .order_by(Expr::col(task::Column::Score).sum(), Order::Desc)
+            .into_model::<RepoPoints>()
+            .all(self.get_connection())
+            .await?;
+
+        let year_month = Expr::col(monthly_score::Column::Year)
+            .mul(100)
+            .add(Expr::col(monthly_score::Column::Month));
+        let (start, end) = filter.month_range();
+        let mut query = monthly_score::Entity::find()
+            .select_only()
+            .column_as(monthly_score::Column::NewScore.sum(), "new_score")
+            .column_as(
+                monthly_score::Column::ConsumptionScore.sum(),
+                "consumption_score",
+            )
+            .column_as(monthly_score::Column::Exchanged.sum(), "exchanged")
+            .filter(program_condition(filter.program_id));
+        if let Some(start) = start {
+            query = query.filter(Expr::expr(year_month.clone()).gte(start));
+        }
+        if let Some(end) = end {
+            query = query.filter(Expr::expr(year_month).lte(end));
+        }
+        let issued = query
+            .into_model::<IssuedPoints>()
+            .one(self.get_connection())
+            .await?
+            .unwrap_or_default();
+        Ok(PointsStats {
+            repositories,
+            issued,
+        })
+    }
+}
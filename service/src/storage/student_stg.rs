@@ -1,13 +1,28 @@
 use std::sync::Arc;
 
-use chrono::{Datelike, NaiveDate, Utc};
-use entity::student;
+use chrono::{NaiveDate, Utc};
+use common::date::program_today;
+use entity::{
+    monthly_score, payout_item, redemption_request, score_adjustment, student, student_change_log,
+    student_contract, task,
+};
 use sea_orm::{
-    ActiveModelTrait, ActiveValue::NotSet, ColumnTrait, DatabaseConnection, EntityTrait,
-    IntoActiveModel, QueryFilter, Set,
+    ActiveModelTrait,
+    ActiveValue::NotSet,
+    ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    IntoActiveModel, ModelTrait, PaginatorTrait, QueryFilter, QueryOrder, Set, SqlErr,
+    TransactionTrait,
+    sea_query::{Expr, Func},
 };
 
-use crate::ospp::ValidateStudentRes;
+use crate::{
+    model::{
+        contract::{parse_contract_deadline, renewal_start, within_contract},
+        student::{ADMIN_SOURCE, StudentFilter, StudentPage, StudentStatus, UpdateStudent},
+        student_sync::{FieldChange, OSPP_SYNC_SOURCE, OsppStudent, student_changes},
+    },
+    ospp::ValidateStudentRes,
+};
 #[derive(Clone)]
 pub struct StudentStorage {
     connection: Arc<DatabaseConnection>,
@@ -22,15 +37,154 @@ impl StudentStorage {
         StudentStorage { connection }
     }
 
+    /// 状态为 active 的学生，program_id 为空时不区分项目
     pub async fn get_active_students(
         &self,
+        program_id: Option<i32>,
     ) -> Result<Vec<student::Model>, anyhow::Error> {
-        let record = student::Entity::find()
-            .all(self.get_connection())
-            .await?;
+        let mut query = student::Entity::find()
+            .filter(student::Column::Status.eq(StudentStatus::Active.as_str()));
+        if let Some(program_id) = program_id {
+            query = query.filter(student::Column::ProgramId.eq(program_id));
+        }
+        let record = query.all(self.get_connection()).await?;
         Ok(record)
     }
 
+    /// 分页查询学生，按 GitHub ID 排序
+    pub async fn search_students(
+        &self,
+        filter: &StudentFilter,
+    ) -> Result<StudentPage, anyhow::Error> {
+        let mut query = student::Entity::find();
+        if let Some(keyword) = &filter.keyword {
+            let pattern = format!("%{}%", keyword.to_lowercase());
+            let like = |column: student::Column| {
+                Expr::expr(Func::lower(Expr::col(column))).like(pattern.clone())
+            };
+            query = query.filter(
+                Condition::any()
+                    .add(like(student::Column::GithubLogin))
+                    .add(like(student::Column::StudentName))
+                    .add(like(student::Column::Email)),
+            );
+        }
+        if let Some(status) = filter.status {
+            query = query.filter(student::Column::Status.eq(status.as_str()));
+        }
+        if let Some(program_id) = filter.program_id {
+            query = query.filter(student::Column::ProgramId.eq(program_id));
+        }
+        let paginator = query
+            .order_by_asc(student::Column::GithubLogin)
+            .paginate(self.get_connection(), filter.page_size);
+        let total = paginator.num_items().await?;
+        let items = paginator.fetch_page(filter.page - 1).await?;
+        Ok(StudentPage {
+            total,
+            page: filter.page,
+            page_size: filter.page_size,
+            items,
+        })
+    }
+
+    /// 修改学生信息并记录变更；修改 GitHub ID 时同步更新各表中的学生 ID
+    pub async fn update_student(
+        &self,
+        login: &str,
+        update: UpdateStudent,
+    ) -> Result<student::Model, anyhow::Error> {
+        let student = self.get_student_by_login(login).await?.ok_or_else(|| {
+            DbErr::RecordNotFound(format!("Student not found for github_login {}", login))
+        })?;
+        let new_login = update.github_login.filter(|new_login| new_login != login);
+
+        let mut changes = vec![];
+        let mut a_model = student.clone().into_active_model();
+        if let Some(name) = update
+            .student_name
+            .filter(|name| *name != student.student_name)
+        {
+            changes.push(FieldChange::new(
+                "student_name",
+                Some(student.student_name.clone()),
+                Some(name.clone()),
+            ));
+            a_model.student_name = Set(name);
+        }
+        if let Some(email) = update.email.filter(|email| *email != student.email) {
+            changes.push(FieldChange::new(
+                "email",
+                Some(student.email.clone()),
+                Some(email.clone()),
+            ));
+            a_model.email = Set(email);
+        }
+        if let Some(new_login) = &new_login {
+            changes.push(FieldChange::new(
+                "github_login",
+                Some(login.to_owned()),
+                Some(new_login.clone()),
+            ));
+            a_model.github_login = Set(new_login.clone());
+        }
+        if changes.is_empty() {
+            return Ok(student);
+        }
+        a_model.update_at = Set(Utc::now().naive_utc());
+
+        let txn = self.get_connection().begin().await?;
+        // 依赖 github_login 唯一索引判断重名，避免先查后写的并发问题
+        let updated =
+            a_model
+                .update(&txn)
+                .await
+                .map_err(|err| match (err.sql_err(), &new_login) {
+                    (Some(SqlErr::UniqueConstraintViolation(_)), Some(new_login)) => {
+                        anyhow::anyhow!("github_login already exists: {}", new_login)
+                    }
+                    _ => err.into(),
+                })?;
+        if let Some(new_login) = &new_login {
+            rename_student_login(&txn, login, new_login).await?;
+        }
+        txn.commit().await?;
+        self.record_changes(&updated.github_login, changes, ADMIN_SOURCE)
+            .await?;
+        Ok(updated)
+    }
+
+    /// 变更学生状态并记录原因，非 Active 的学生不再认领任务、参与定时同步和接收群发邮件
+    pub async fn change_status(
+        &self,
+        login: &str,
+        status: StudentStatus,
+        reason: &str,
+    ) -> Result<student::Model, anyhow::Error> {
+        let student = self.get_student_by_login(login).await?.ok_or_else(|| {
+            DbErr::RecordNotFound(format!("Student not found for github_login {}", login))
+        })?;
+        let current = StudentStatus::from(student.status.clone());
+        if !current.can_transition_to(status) {
+            return Err(anyhow::anyhow!(
+                "student {} can not change status from {} to {}",
+                login,
+                current.as_str(),
+                status.as_str()
+            ));
+        }
+        let change = FieldChange::new("status", Some(student.status.clone()), Some(status.into()))
+            .with_reason(reason);
+        let mut a_model = student.into_active_model();
+        a_model.status = Set(status.into());
+        a_model.status_reason = Set(Some(reason.to_owned()));
+        a_model.update_at = Set(Utc::now().naive_utc());
+        let updated = a_model.update(self.get_connection()).await?;
+        self.record_changes(login, vec![change], ADMIN_SOURCE)
+            .await?;
+        Ok(updated)
+    }
+
     pub async fn get_student_by_login(
         &self,
         login: &str,
@@ -42,20 +196,29 @@ impl StudentStorage {
         Ok(record)
     }
 
+    pub async fn get_students_by_logins(
+        &self,
+        logins: Vec<String>,
+    ) -> Result<Vec<student::Model>, anyhow::Error> {
+        let students = student::Entity::find()
+            .filter(student::Column::GithubLogin.is_in(logins))
+            .all(self.get_connection())
+            .await?;
+        Ok(students)
+    }
+
+    /// 校验通过后登记学生，已登记的学生只更新合同截止日期，不改变所属项目
     pub async fn insert_or_update_student(
         &self,
         login: &str,
+        program_id: i32,
         data: ValidateStudentRes,
     ) -> Result<(), anyhow::Error> {
-        let contract_deadline = if let Some(contract_deadline) = data.contract_deadline {
-            let contract_deadline =
-                NaiveDate::parse_from_str(&contract_deadline, "%Y-%m-%d %H:%M:%S")
-                    .expect("time formart not match");
-            let contract_deadline = contract_deadline.with_day(1).unwrap();
-            Some(contract_deadline)
-        } else {
-            None
-        };
+        let contract_deadline = data
+            .contract_deadline
+            .as_deref()
+            .map(parse_contract_deadline)
+            .transpose()?;
 
         let now = Utc::now().naive_utc();
         let student = self.get_student_by_login(login).await?;
@@ -70,12 +233,286 @@ impl StudentStorage {
                 github_login: Set(login.to_owned()),
                 student_name: Set(data.student_name.unwrap_or_default()),
                 contract_end_date: Set(contract_deadline),
+                contract_start_date: Set(None),
                 create_at: Set(now),
                 update_at: Set(now),
                 email: Set(data.email.unwrap_or_default()),
+                ospp_missing: Set(false),
+                synced_at: Set(Some(now)),
+                status: Set(StudentStatus::Active.into()),
+                status_reason: Set(None),
+                program_id: Set(program_id),
             };
             new_stu.insert(self.get_connection()).await?;
         }
+
+        if let Some(end_date) = contract_deadline {
+            self.sync_contract(login, end_date).await?;
+        }
+        Ok(())
+    }
+
+    /// OSPP 只返回截止日期，截止日期延后视为续签，提前视为对当前合同的更正
+    async fn sync_contract(&self, login: &str, end_date: NaiveDate) -> Result<(), anyhow::Error> {
+        let start_date = match self.list_contracts(login).await?.pop() {
+            Some(latest) if latest.end_date == end_date => latest.start_date,
+            Some(latest) if latest.end_date < end_date => {
+                Some(renewal_start(latest.end_date, program_today()))
+            }
+            Some(latest) => {
+                let start_date = latest.start_date;
+                latest.delete(self.get_connection()).await?;
+                start_date
+            }
+            None => None,
+        };
+        self.record_contract(login, start_date, end_date).await?;
         Ok(())
     }
+
+    /// 用 OSPP 返回的信息更新已有学生的姓名、邮箱和合同截止日期，返回记录的变更
+    pub async fn sync_student(
+        &self,
+        login: &str,
+        data: ValidateStudentRes,
+    ) -> Result<Vec<student_change_log::Model>, anyhow::Error> {
+        let Some(student) = self.get_student_by_login(login).await? else {
+            return Ok(vec![]);
+        };
+        let contract_end_date = data
+            .contract_deadline
+            .as_deref()
+            .map(parse_contract_deadline)
+            .transpose()?;
+        let ospp = OsppStudent {
+            student_name: data.student_name,
+            email: data.email,
+            contract_end_date,
+        };
+        let changes = student_changes(&student, &ospp);
+
+        let now = Utc::now().naive_utc();
+        let mut a_model = student.into_active_model();
+        for change in &changes {
+            match (change.field.as_str(), change.new_value.clone()) {
+                ("student_name", Some(name)) => a_model.student_name = Set(name),
+                ("email", Some(email)) => a_model.email = Set(email),
+                ("ospp_missing", _) => a_model.ospp_missing = Set(false),
+                _ => {}
+            }
+        }
+        if !changes.is_empty() {
+            a_model.update_at = Set(now);
+        }
+        a_model.synced_at = Set(Some(now));
+        a_model.update(self.get_connection()).await?;
+
+        if let Some(end_date) = contract_end_date
+            && changes
+                .iter()
+                .any(|change| change.field == "contract_end_date")
+        {
+            self.sync_contract(login, end_date).await?;
+        }
+        self.record_changes(login, changes, OSPP_SYNC_SOURCE).await
+    }
+
+    /// 标记 OSPP 不再认可的学生，已标记过时返回 None
+    pub async fn mark_ospp_missing(
+        &self,
+        login: &str,
+    ) -> Result<Option<student_change_log::Model>, anyhow::Error> {
+        let Some(student) = self.get_student_by_login(login).await? else {
+            return Ok(None);
+        };
+        let now = Utc::now().naive_utc();
+        let already_missing = student.ospp_missing;
+        let mut a_model = student.into_active_model();
+        a_model.synced_at = Set(Some(now));
+        if !already_missing {
+            a_model.ospp_missing = Set(true);
+            a_model.update_at = Set(now);
+        }
+        a_model.update(self.get_connection()).await?;
+        if already_missing {
+            return Ok(None);
+        }
+        let change = FieldChange::new(
+            "ospp_missing",
+            Some(false.to_string()),
+            Some(true.to_string()),
+        );
+        Ok(self
+            .record_changes(login, vec![change], OSPP_SYNC_SOURCE)
+            .await?
+            .pop())
+    }
+
+    pub async fn record_changes(
+        &self,
+        login: &str,
+        changes: Vec<FieldChange>,
+        source: &str,
+    ) -> Result<Vec<student_change_log::Model>, anyhow::Error> {
+        let now = Utc::now().naive_utc();
+        let mut records = vec![];
+        for change in changes {
+            let record = student_change_log::ActiveModel {
+                id: NotSet,
+                github_login: Set(login.to_owned()),
+                field: Set(change.field),
+                old_value: Set(change.old_value),
+                new_value: Set(change.new_value),
+                source: Set(source.to_owned()),
+                reason: Set(change.reason),
+                create_at: Set(now),
+            };
+            records.push(record.insert(self.get_connection()).await?);
+        }
+        Ok(records)
+    }
+
+    /// 学生信息变更记录，按时间倒序
+    pub async fn list_change_logs(
+        &self,
+        login: Option<&str>,
+    ) -> Result<Vec<student_change_log::Model>, anyhow::Error> {
+        let mut query = student_change_log::Entity::find();
+        if let Some(login) = login {
+            query = query.filter(student_change_log::Column::GithubLogin.eq(login));
+        }
+        let records = query
+            .order_by_desc(student_change_log::Column::Id)
+            .all(self.get_connection())
+            .await?;
+        Ok(records)
+    }
+
+    pub async fn list_contracts(
+        &self,
+        login: &str,
+    ) -> Result<Vec<student_contract::Model>, anyhow::Error> {
+        let records = student_contract::Entity::find()
+            .filter(student_contract::Column::GithubLogin.eq(login))
+            .order_by_asc(student_contract::Column::EndDate)
+            .all(self.get_connection())
+            .await?;
+        Ok(records)
+    }
+
+    /// 记录一份合同，截止日期相同视为同一份合同；学生表同步为最新一份合同
+    pub async fn record_contract(
+        &self,
+        login: &str,
+        start_date: Option<NaiveDate>,
+        end_date: NaiveDate,
+    ) -> Result<student_contract::Model, anyhow::Error> {
+        let student = self.get_student_by_login(login).await?.ok_or_else(|| {
+            DbErr::RecordNotFound(format!("Student not found for github_login {}", login))
+        })?;
+        let now = Utc::now().naive_utc();
+        let existing = student_contract::Entity::find()
+            .filter(student_contract::Column::GithubLogin.eq(login))
+            .filter(student_contract::Column::EndDate.eq(end_date))
+            .one(self.get_connection())
+            .await?;
+        let contract = if let Some(existing) = existing {
+            let mut a_model = existing.into_active_model();
+            a_model.start_date = Set(start_date);
+            a_model.update_at = Set(now);
+            a_model.update(self.get_connection()).await?
+        } else {
+            let contract = student_contract::ActiveModel {
+                id: NotSet,
+                github_login: Set(login.to_owned()),
+                start_date: Set(start_date),
+                end_date: Set(end_date),
+                create_at: Set(now),
+                update_at: Set(now),
+            };
+            contract.insert(self.get_connection()).await?
+        };
+
+        if let Some(latest) = self.list_contracts(login).await?.last() {
+            let mut a_model = student.into_active_model();
+            a_model.contract_start_date = Set(latest.start_date);
+            a_model.contract_end_date = Set(Some(latest.end_date));
+            a_model.update_at = Set(now);
+            a_model.update(self.get_connection()).await?;
+        }
+        Ok(contract)
+    }
+
+    /// 指定日期是否在学生的合同期内
+    pub async fn is_within_contract(
+        &self,
+        login: &str,
+        date: NaiveDate,
+    ) -> Result<bool, anyhow::Error> {
+        let contracts = self.list_contracts(login).await?;
+        if contracts.is_empty() {
+            // 没有合同记录的老数据以学生表中的截止日期为准
+            let student = self.get_student_by_login(login).await?;
+            return Ok(student
+                .and_then(|student| student.contract_end_date)
+                .is_some_and(|end_date| date <= end_date));
+        }
+        Ok(within_contract(&contracts, date))
+    }
+}
+
+/// 学生 GitHub ID 变更时同步更新引用该 ID 的记录
+async fn rename_student_login<C: ConnectionTrait>(
+    db: &C,
+    login: &str,
+    new_login: &str,
+) -> Result<(), DbErr> {
+    student_contract::Entity::update_many()
+        .col_expr(
+            student_contract::Column::GithubLogin,
+            Expr::value(new_login),
+        )
+        .filter(student_contract::Column::GithubLogin.eq(login))
+        .exec(db)
+        .await?;
+    student_change_log::Entity::update_many()
+        .col_expr(
+            student_change_log::Column::GithubLogin,
+            Expr::value(new_login),
+        )
+        .filter(student_change_log::Column::GithubLogin.eq(login))
+        .exec(db)
+        .await?;
+    task::Entity::update_many()
+        .col_expr(task::Column::StudentGithubLogin, Expr::value(new_login))
+        .filter(task::Column::StudentGithubLogin.eq(login))
+        .exec(db)
+        .await?;
+    monthly_score::Entity::update_many()
+        .col_expr(monthly_score::Column::GithubLogin, Expr::value(new_login))
+        .filter(monthly_score::Column::GithubLogin.eq(login))
+        .exec(db)
+        .await?;
+    score_adjustment::Entity::update_many()
+        .col_expr(
+            score_adjustment::Column::GithubLogin,
+            Expr::value(new_login),
+        )
+        .filter(score_adjustment::Column::GithubLogin.eq(login))
+        .exec(db)
+        .await?;
+    redemption_request::Entity::update_many()
+        .col_expr(
+            redemption_request::Column::GithubLogin,
+            Expr::value(new_login),
+        )
+        .filter(redemption_request::Column::GithubLogin.eq(login))
+        .exec(db)
+        .await?;
+    payout_item::Entity::update_many()
+        .col_expr(payout_item::Column::GithubLogin, Expr::value(new_login))
+        .filter(payout_item::Column::GithubLogin.eq(login))
+        .exec(db)
+        .await?;
+    Ok(())
 }
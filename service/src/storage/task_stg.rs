@@ -1,17 +1,34 @@
 use std::sync::Arc;
 
-use chrono::{Datelike, Utc};
+use chrono::Utc;
+use common::{date::current_year_month, metrics::record_task_transition};
 use entity::{sea_orm_active_enums::TaskStatus, task};
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
-    Set,
+    ActiveEnum, ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, QuerySelect, Set,
+    sea_query::{Expr, Order},
 };
 
+use crate::model::leaderboard::{LeaderboardFilter, StudentTaskRank};
+
 #[derive(Clone)]
 pub struct TaskStorage {
     connection: Arc<DatabaseConnection>,
 }
 
+/// 计入积分的任务：在合同期内完成，或合同期外完成但导师确认
+pub(crate) fn payable_condition() -> Condition {
+    Condition::any()
+        .add(task::Column::OutOfContract.eq(false))
+        .add(task::Column::ContractOverride.eq(true))
+}
+
+/// 记录任务进入的新状态
+fn transitioned(task: task::Model) -> task::Model {
+    record_task_transition(task.task_status.to_value());
+    task
+}
+
 impl TaskStorage {
     pub fn get_connection(&self) -> &DatabaseConnection {
         &self.connection
@@ -26,7 +43,7 @@ impl TaskStorage {
         active_model: task::ActiveModel,
     ) -> Result<task::Model, anyhow::Error> {
         let task = active_model.insert(self.get_connection()).await?;
-        Ok(task)
+        Ok(transitioned(task))
     }
 
     pub async fn update_score(
@@ -64,17 +81,61 @@ impl TaskStorage {
         &self,
         finish_year: i32,
         finish_month: i32,
+        program_id: Option<i32>,
     ) -> Result<Vec<task::Model>, anyhow::Error> {
-        let task = task::Entity::find()
+        let mut query = task::Entity::find();
+        if let Some(program_id) = program_id {
+            query = query.filter(task::Column::ProgramId.eq(program_id));
+        }
+        let task = query
             .filter(task::Column::FinishYear.eq(finish_year))
             .filter(task::Column::FinishMonth.eq(finish_month))
             .filter(task::Column::TaskStatus.eq(TaskStatus::Finished))
+            .filter(payable_condition())
             .order_by_asc(task::Column::StudentGithubLogin)
             .all(self.get_connection())
             .await?;
         Ok(task)
     }
 
+    /// 按学生聚合已完成任务的分数和数量，可按仓库或导师过滤
+    pub async fn finished_task_leaderboard(
+        &self,
+        filter: &LeaderboardFilter,
+    ) -> Result<Vec<StudentTaskRank>, anyhow::Error> {
+        let mut query = task::Entity::find()
+            .select_only()
+            .column_as(task::Column::StudentGithubLogin, "github_login")
+            .column_as(task::Column::Score.sum(), "points")
+            .column_as(task::Column::Id.count(), "finished_tasks")
+            .filter(task::Column::TaskStatus.eq(TaskStatus::Finished))
+            .filter(task::Column::StudentGithubLogin.is_not_null())
+            .filter(payable_condition())
+            .filter(task::Column::FinishYear.eq(filter.year));
+        if let Some(month) = filter.month {
+            query = query.filter(task::Column::FinishMonth.eq(month));
+        }
+        if let Some(owner) = &filter.owner {
+            query = query.filter(task::Column::Owner.eq(owner));
+        }
+        if let Some(repo) = &filter.repo {
+            query = query.filter(task::Column::Repo.eq(repo));
+        }
+        if let Some(mentor) = &filter.mentor_github_login {
+            query = query.filter(task::Column::MentorGithubLogin.eq(mentor));
+        }
+        if let Some(program_id) = filter.program_id {
+            query = query.filter(task::Column::ProgramId.eq(program_id));
+        }
+        let ranks = query
+            .group_by(task::Column::StudentGithubLogin)
+            .order_by(Expr::col(task::Column::Score).sum(), Order::Desc)
+            .into_model::<StudentTaskRank>()
+            .all(self.get_connection())
+            .await?;
+        Ok(ranks)
+    }
+
     pub async fn search_task_with_status(
         &self,
         github_repo_id: i64,
@@ -103,6 +164,21 @@ impl TaskStorage {
         Ok(tasks)
     }
 
+    /// 学生名下指定状态的全部任务，最近更新的在前
+    pub async fn list_student_tasks(
+        &self,
+        login: &str,
+        status: Vec<TaskStatus>,
+    ) -> Result<Vec<task::Model>, anyhow::Error> {
+        let tasks = task::Entity::find()
+            .filter(task::Column::StudentGithubLogin.eq(login))
+            .filter(task::Column::TaskStatus.is_in(status))
+            .order_by_desc(task::Column::UpdateAt)
+            .all(self.get_connection())
+            .await?;
+        Ok(tasks)
+    }
+
     pub async fn get_student_tasks_with_status_in_month(
         &self,
         login: &str,
@@ -138,7 +214,7 @@ impl TaskStorage {
         task.task_status = Set(TaskStatus::RequestAssign);
         task.update_at = Set(Utc::now().naive_utc());
 
-        Ok(task.update(self.get_connection()).await?)
+        Ok(transitioned(task.update(self.get_connection()).await?))
     }
 
     pub async fn release_task(&self, github_issue_id: i64) -> Result<task::Model, anyhow::Error> {
@@ -149,11 +225,14 @@ impl TaskStorage {
                 "Task not found for issue_id {}",
                 github_issue_id
             )))?;
+        let release_count = task.release_count;
         let mut task: task::ActiveModel = task.into();
         task.student_github_login = Set(None);
         task.task_status = Set(TaskStatus::Open);
+        task.assigned_at = Set(None);
+        task.release_count = Set(release_count + 1);
         task.update_at = Set(Utc::now().naive_utc());
-        Ok(task.update(self.get_connection()).await?)
+        Ok(transitioned(task.update(self.get_connection()).await?))
     }
 
     pub async fn intern_approve(&self, github_issue_id: i64) -> Result<task::Model, anyhow::Error> {
@@ -166,8 +245,9 @@ impl TaskStorage {
             )))?;
         let mut task: task::ActiveModel = task.into();
         task.task_status = Set(TaskStatus::Assigned);
+        task.assigned_at = Set(Some(Utc::now().naive_utc()));
         task.update_at = Set(Utc::now().naive_utc());
-        Ok(task.update(self.get_connection()).await?)
+        Ok(transitioned(task.update(self.get_connection()).await?))
     }
 
     pub async fn request_complete(
@@ -184,7 +264,7 @@ impl TaskStorage {
         let mut task: task::ActiveModel = task.into();
         task.task_status = Set(TaskStatus::RequestFinish);
         task.update_at = Set(Utc::now().naive_utc());
-        Ok(task.update(self.get_connection()).await?)
+        Ok(transitioned(task.update(self.get_connection()).await?))
     }
 
     pub async fn intern_done(&self, github_issue_id: i64) -> Result<task::Model, anyhow::Error> {
@@ -195,14 +275,90 @@ impl TaskStorage {
                 "Task not found for issue_id {}",
                 github_issue_id
             )))?;
+        let (year, month) = current_year_month();
         let mut task: task::ActiveModel = task.into();
         task.task_status = Set(TaskStatus::Finished);
-        task.finish_year = Set(Some(Utc::now().year()));
-        task.finish_month = Set(Some(Utc::now().month() as i32));
+        task.finish_year = Set(Some(year));
+        task.finish_month = Set(Some(month));
+        task.finished_at = Set(Some(Utc::now().naive_utc()));
+        task.update_at = Set(Utc::now().naive_utc());
+        Ok(transitioned(task.update(self.get_connection()).await?))
+    }
+
+    /// 标记任务在学生合同期外完成，不计入积分发放
+    pub async fn mark_out_of_contract(
+        &self,
+        github_issue_id: i64,
+    ) -> Result<task::Model, anyhow::Error> {
+        let task = self
+            .search_task_with_issue_id(github_issue_id)
+            .await?
+            .ok_or(DbErr::RecordNotFound(format!(
+                "Task not found for issue_id {}",
+                github_issue_id
+            )))?;
+        let mut task: task::ActiveModel = task.into();
+        task.out_of_contract = Set(true);
         task.update_at = Set(Utc::now().naive_utc());
         Ok(task.update(self.get_connection()).await?)
     }
 
+    /// 导师确认合同期外完成的任务仍然计入积分
+    pub async fn contract_override(
+        &self,
+        github_issue_id: i64,
+        mentor_login: &str,
+    ) -> Result<task::Model, anyhow::Error> {
+        let task = self
+            .search_task_with_issue_id(github_issue_id)
+            .await?
+            .ok_or(DbErr::RecordNotFound(format!(
+                "Task not found for issue_id {}",
+                github_issue_id
+            )))?;
+        if task.mentor_github_login != mentor_login {
+            return Err(anyhow::anyhow!(
+                "only mentor {} can override this task",
+                task.mentor_github_login
+            ));
+        }
+        if task.task_status != TaskStatus::Finished || !task.out_of_contract {
+            return Err(anyhow::anyhow!(
+                "task {} is not finished out of contract",
+                github_issue_id
+            ));
+        }
+        if task.contract_override {
+            return Err(anyhow::anyhow!(
+                "task {} already overridden",
+                github_issue_id
+            ));
+        }
+        let mut task: task::ActiveModel = task.into();
+        task.contract_override = Set(true);
+        task.contract_override_by = Set(Some(mentor_login.to_owned()));
+        task.update_at = Set(Utc::now().naive_utc());
+        Ok(task.update(self.get_connection()).await?)
+    }
+
+    /// 查询指定月份合同期外完成、尚未被导师确认的任务
+    pub async fn search_out_of_contract_task(
+        &self,
+        finish_year: i32,
+        finish_month: i32,
+    ) -> Result<Vec<task::Model>, anyhow::Error> {
+        let tasks = task::Entity::find()
+            .filter(task::Column::FinishYear.eq(finish_year))
+            .filter(task::Column::FinishMonth.eq(finish_month))
+            .filter(task::Column::TaskStatus.eq(TaskStatus::Finished))
+            .filter(task::Column::OutOfContract.eq(true))
+            .filter(task::Column::ContractOverride.eq(false))
+            .order_by_asc(task::Column::StudentGithubLogin)
+            .all(self.get_connection())
+            .await?;
+        Ok(tasks)
+    }
+
     pub async fn intern_close(&self, github_issue_id: i64) -> Result<task::Model, anyhow::Error> {
         let task = self
             .search_task_with_issue_id(github_issue_id)
@@ -214,6 +370,7 @@ impl TaskStorage {
         if task.task_status != TaskStatus::Finished {
             let task: task::ActiveModel = task.clone().into();
             task.delete(self.get_connection()).await?;
+            record_task_transition(TaskStatus::Invalid.to_value());
         }
         Ok(task)
     }